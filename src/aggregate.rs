@@ -0,0 +1,161 @@
+use std::{borrow::Borrow, ops::RangeBounds};
+
+use crate::llrb::{is_above, is_below, Llrb};
+
+/// Aggregate summarizes the entries in a sub-tree of [`Llrb`] instance.
+/// Every node in the tree holds the aggregate of its sub-tree, which is
+/// kept up to date through inserts, deletes and rotations.
+///
+/// Aggregate shall form a monoid, that is, `combine` is associative and
+/// `identity` is its neutral element. The aggregate for a sub-tree is
+/// computed, in sort order, as `left.combine(lift(node)).combine(right)`,
+/// hence `combine` need not be commutative.
+///
+/// Sum of values in a range:
+/// ```
+/// use llrb_index::{Aggregate, Llrb};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Sum(u64);
+///
+/// impl<K> Aggregate<K, u64> for Sum {
+///     fn identity() -> Self {
+///         Sum(0)
+///     }
+///     fn lift(_key: &K, value: &u64) -> Self {
+///         Sum(*value)
+///     }
+///     fn combine(&self, other: &Self) -> Self {
+///         Sum(self.0 + other.0)
+///     }
+/// }
+///
+/// let mut llrb: Llrb<u64, u64, Sum> = Llrb::new_aggregate("myinstance");
+/// (0..100).for_each(|key| { llrb.set(key, key * 10); });
+/// assert_eq!(llrb.fold_range(10..20), Sum((10..20).map(|x| x * 10).sum()));
+/// ```
+pub trait Aggregate<K, V>: Clone {
+    /// Aggregate for an empty sub-tree.
+    fn identity() -> Self;
+
+    /// Aggregate for a single entry.
+    fn lift(key: &K, value: &V) -> Self;
+
+    /// Combine the aggregates of two adjacent sub-trees, `self` sorting
+    /// before `other`.
+    fn combine(&self, other: &Self) -> Self;
+
+    /// Number of entries in the sub-tree, if this aggregate counts them.
+    /// Counting aggregates, like [`Count`], enable sampling in O(log n),
+    /// refer to [`Llrb::sample_uniform`].
+    fn count(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Default, for instances that maintain no aggregate.
+impl<K, V> Aggregate<K, V> for () {
+    #[inline]
+    fn identity() -> Self {}
+
+    #[inline]
+    fn lift(_key: &K, _value: &V) -> Self {}
+
+    #[inline]
+    fn combine(&self, _other: &Self) -> Self {}
+}
+
+/// Count entries in a sub-tree, so that entries can be located by their
+/// rank in O(log n). Refer to [`Llrb::sample_uniform`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Count(pub usize);
+
+impl<K, V> Aggregate<K, V> for Count {
+    #[inline]
+    fn identity() -> Self {
+        Count(0)
+    }
+
+    #[inline]
+    fn lift(_key: &K, _value: &V) -> Self {
+        Count(1)
+    }
+
+    #[inline]
+    fn combine(&self, other: &Self) -> Self {
+        Count(self.0 + other.0)
+    }
+
+    #[inline]
+    fn count(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
+
+/// Aggregate operations on Llrb instance.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Return the aggregate over all entries from low to high, in
+    /// O(log n), using the aggregates held by sub-trees. Entries whose
+    /// time-to-live has lapsed, but not yet removed, are included.
+    pub fn fold_range<Q, R>(&self, range: R) -> A
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        // walk down to the node where paths to low and high diverge.
+        let mut node = self.root.as_deref();
+        let split = loop {
+            match node {
+                None => return A::identity(),
+                Some(nref) if !is_above(&range, &nref.key) => node = nref.right_deref(),
+                Some(nref) if !is_below(&range, &nref.key) => node = nref.left_deref(),
+                Some(nref) => break nref,
+            }
+        };
+
+        // entries above low, from the left sub-tree, in reverse order.
+        let mut lacc = A::identity();
+        let mut node = split.left_deref();
+        while let Some(nref) = node {
+            if is_above(&range, &nref.key) {
+                let mut agg = A::lift(&nref.key, &nref.value);
+                if let Some(right) = nref.right_deref() {
+                    agg = agg.combine(&right.agg);
+                }
+                lacc = agg.combine(&lacc);
+                node = nref.left_deref();
+            } else {
+                node = nref.right_deref();
+            }
+        }
+
+        // entries below high, from the right sub-tree, in order.
+        let mut racc = A::identity();
+        let mut node = split.right_deref();
+        while let Some(nref) = node {
+            if is_below(&range, &nref.key) {
+                let mut agg = A::lift(&nref.key, &nref.value);
+                if let Some(left) = nref.left_deref() {
+                    agg = left.agg.combine(&agg);
+                }
+                racc = racc.combine(&agg);
+                node = nref.right_deref();
+            } else {
+                node = nref.left_deref();
+            }
+        }
+
+        let agg = A::lift(&split.key, &split.value);
+        lacc.combine(&agg).combine(&racc)
+    }
+}
+
+#[cfg(test)]
+#[path = "aggregate_test.rs"]
+mod aggregate_test;
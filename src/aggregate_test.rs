@@ -0,0 +1,100 @@
+use std::ops::Bound;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Sum(i64);
+
+impl Aggregate<i64, i64> for Sum {
+    fn identity() -> Self {
+        Sum(0)
+    }
+
+    fn lift(_key: &i64, value: &i64) -> Self {
+        Sum(*value)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Sum(self.0 + other.0)
+    }
+}
+
+// Non-commutative aggregate, keys in sort order.
+#[derive(Clone, Debug, PartialEq)]
+struct Keys(Vec<i64>);
+
+impl Aggregate<i64, i64> for Keys {
+    fn identity() -> Self {
+        Keys(vec![])
+    }
+
+    fn lift(key: &i64, _value: &i64) -> Self {
+        Keys(vec![*key])
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        let mut keys = self.0.clone();
+        keys.extend_from_slice(&other.0);
+        Keys(keys)
+    }
+}
+
+#[test]
+fn test_fold_range() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64, Sum> = Llrb::new_aggregate("test-llrb");
+    let mut keys: Llrb<i64, i64, Keys> = Llrb::new_aggregate("test-keys");
+    assert_eq!(llrb.fold_range(..), Sum(0));
+    for _ in 0..2_000 {
+        let (key, value) = (rng.gen_range(0, 1_000), rng.gen_range(-100, 100));
+        match rng.gen_range(0, 3) {
+            0 => {
+                llrb.delete(&key);
+                keys.delete(&key);
+            }
+            _ => {
+                llrb.set(key, value);
+                keys.set(key, value);
+            }
+        }
+    }
+    assert!(llrb.validate().is_ok());
+
+    let total: i64 = llrb.iter().map(|(_, v)| v).sum();
+    assert_eq!(llrb.fold_range(..), Sum(total));
+    let all: Vec<i64> = keys.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys.fold_range(..), Keys(all));
+
+    for _ in 0..1_000 {
+        let low = match rng.gen_range(0, 3) {
+            0 => Bound::Included(rng.gen_range(-10, 1_010)),
+            1 => Bound::Excluded(rng.gen_range(-10, 1_010)),
+            _ => Bound::Unbounded,
+        };
+        let high = match rng.gen_range(0, 3) {
+            0 => Bound::Included(rng.gen_range(-10, 1_010)),
+            1 => Bound::Excluded(rng.gen_range(-10, 1_010)),
+            _ => Bound::Unbounded,
+        };
+        let valid = match (low, high) {
+            (Bound::Included(l), Bound::Included(h)) => l <= h,
+            (Bound::Included(l), Bound::Excluded(h)) => l <= h,
+            (Bound::Excluded(l), Bound::Included(h)) => l <= h,
+            (Bound::Excluded(l), Bound::Excluded(h)) => l < h,
+            _ => true,
+        };
+        if !valid {
+            continue;
+        }
+        let sum: i64 = llrb.range((low, high)).map(|(_, v)| v).sum();
+        assert_eq!(llrb.fold_range((low, high)), Sum(sum));
+        let refs: Vec<i64> = keys.range((low, high)).map(|(k, _)| k).collect();
+        assert_eq!(keys.fold_range((low, high)), Keys(refs));
+    }
+}
@@ -0,0 +1,267 @@
+use std::time::Instant;
+
+use crate::build::{black_height, build_tree, drain_tree};
+use crate::error::Error;
+use crate::llrb::{Llrb, Node};
+use crate::metrics::Counter;
+
+// Batches touching at least one in BULK_RATIO entries are merged with
+// the entries, and the tree is rebuilt.
+const BULK_RATIO: usize = 4;
+
+/// Write operation on a single entry, that can be batched.
+#[derive(Clone)]
+enum Write<K, V> {
+    Set(K, V),
+    Create(K, V),
+    Delete(K),
+}
+
+impl<K, V> Write<K, V> {
+    fn key(&self) -> &K {
+        match self {
+            Write::Set(key, _) | Write::Create(key, _) | Write::Delete(key) => key,
+        }
+    }
+}
+
+/// WriteBatch is a group of write operations, that can be applied
+/// on [`Llrb`] instance atomically, refer to [`Llrb::apply`].
+#[derive(Clone)]
+pub struct WriteBatch<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    writes: Vec<Write<K, V>>,
+}
+
+impl<K, V> Default for WriteBatch<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    fn default() -> Self {
+        WriteBatch { writes: vec![] }
+    }
+}
+
+impl<K, V> WriteBatch<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Create an empty batch.
+    pub fn new() -> WriteBatch<K, V> {
+        Default::default()
+    }
+
+    /// Add a [`Llrb::set`] operation to this batch.
+    pub fn set(&mut self, key: K, value: V) {
+        self.writes.push(Write::Set(key, value))
+    }
+
+    /// Add a [`Llrb::create`] operation to this batch. When applied, key
+    /// must not be present in the index.
+    pub fn create(&mut self, key: K, value: V) {
+        self.writes.push(Write::Create(key, value))
+    }
+
+    /// Add a [`Llrb::delete`] operation to this batch.
+    pub fn delete(&mut self, key: K) {
+        self.writes.push(Write::Delete(key))
+    }
+
+    /// Return number of operations in this batch.
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Check whether this batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
+/// Batch write operations on Llrb instance.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Apply all operations in `batch`, or none of them. Operations on
+    /// the same key are applied in the order they were added to the
+    /// batch. Preconditions for all operations are validated before
+    /// touching the index, if any of them fails, return error and leave
+    /// the index untouched:
+    ///
+    /// * `create` requires the key to be absent, else return
+    ///   [`Error::OverwriteKey`].
+    ///
+    /// Batch is sorted by key, and only the last operation on a key is
+    /// applied. A batch touching a quarter of the entries, or more, is
+    /// merged with the entries in a single pass, and the tree is rebuilt
+    /// bottom-up reusing its nodes, which is faster than applying the
+    /// operations one by one. Bounded instances, and instances with
+    /// observers, always apply the operations one by one.
+    ///
+    /// On success, return the number of operations applied.
+    pub fn apply(&mut self, batch: WriteBatch<K, V>) -> Result<usize, Error<K>> {
+        let n = batch.len();
+        let mut writes = batch.writes;
+        // stable sort, preserves the order of operations on same key.
+        writes.sort_by(|a, b| a.key().cmp(b.key()));
+
+        let firsts: Vec<usize> = (0..writes.len())
+            .filter(|&i| i == 0 || writes[i - 1].key() != writes[i].key())
+            .collect();
+        let bulk = self.capacity().is_none()
+            && !self.observer.is_active()
+            && firsts.len() * BULK_RATIO >= self.n_count;
+        let located = match bulk {
+            true => self.locate(firsts.iter().map(|i| writes[*i].key())),
+            false => vec![],
+        };
+
+        let (mut present, mut group) = (false, 0);
+        for (i, write) in writes.iter().enumerate() {
+            let key = write.key();
+            if i == 0 || writes[i - 1].key() != key {
+                present = match located.get(group) {
+                    Some((_, found)) => *found && !self.is_expired(key),
+                    None => self.contains_key(key),
+                };
+                group += 1;
+            }
+            present = match write {
+                Write::Create(_, _) if present => return Err(Error::OverwriteKey),
+                Write::Set(_, _) | Write::Create(_, _) => true,
+                Write::Delete(_) => false,
+            };
+        }
+
+        // only the last operation on a key decides its final state.
+        let mut lasts = Vec::with_capacity(firsts.len());
+        let mut writes = writes.into_iter().peekable();
+        while let Some(write) = writes.next() {
+            match writes.peek() {
+                Some(next) if next.key() == write.key() => (),
+                _ => lasts.push(write),
+            }
+        }
+
+        if bulk {
+            self.merge(lasts, located);
+            return Ok(n);
+        }
+        for write in lasts.into_iter() {
+            match write {
+                Write::Set(key, value) | Write::Create(key, value) => {
+                    self.set(key, value);
+                }
+                Write::Delete(key) => {
+                    self.delete(&key);
+                }
+            }
+        }
+        Ok(n)
+    }
+
+    // Locate sorted `keys` in a single in-order walk over the tree. For
+    // each key, return the number of entries sorting before it, and
+    // whether it is present in the tree.
+    fn locate<'a, I>(&self, keys: I) -> Vec<(usize, bool)>
+    where
+        K: 'a,
+        I: Iterator<Item = &'a K>,
+    {
+        let (mut node, mut stack) = (self.root.as_deref(), vec![]);
+        let (mut rank, mut entry): (usize, Option<&Node<K, V>>) = (0, None);
+        let mut located = vec![];
+        for key in keys {
+            loop {
+                match entry {
+                    Some(nref) if nref.key.lt(key) => {
+                        rank += 1;
+                        entry = None;
+                    }
+                    Some(_) => break,
+                    None => {
+                        while let Some(nref) = node {
+                            stack.push(nref);
+                            node = nref.left_deref();
+                        }
+                        match stack.pop() {
+                            Some(nref) => {
+                                node = nref.right_deref();
+                                entry = Some(nref);
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+            let found = matches!(entry, Some(nref) if nref.key.eq(key));
+            located.push((rank, found));
+        }
+        located
+    }
+
+    // Merge the last operation on every key with the entries in the tree,
+    // and rebuild the tree. Keys are already located, hence entries are
+    // merged without comparing keys, once the tree is taken apart.
+    fn merge(&mut self, writes: Vec<Write<K, V>>, located: Vec<(usize, bool)>) {
+        // deadlines are dropped before the tree is touched, as with set()
+        // and delete(), entries removed after their deadline are counted.
+        let now = Instant::now();
+        if !self.expiry.is_empty() {
+            for (write, (_, found)) in writes.iter().zip(located.iter()) {
+                match self.expiry.remove(write.key()) {
+                    Some(deadline) if *found && deadline <= now => self.n_expired += 1,
+                    _ => (),
+                }
+            }
+        }
+
+        let olds = drain_tree(self.root.take());
+        self.n_count = 0;
+        let mut nodes = Vec::with_capacity(olds.len() + writes.len());
+        let mut olds = olds.into_iter();
+        let mut off = 0;
+        for (write, (rank, found)) in writes.into_iter().zip(located) {
+            nodes.extend(olds.by_ref().take(rank - off));
+            off = rank;
+            let old = if found {
+                off += 1;
+                olds.next().unwrap()
+            } else {
+                None
+            };
+            match (write, old) {
+                (Write::Set(_, value), Some(mut node))
+                | (Write::Create(_, value), Some(mut node)) => {
+                    self.metrics.incr(Counter::Sets);
+                    node.value = value;
+                    nodes.push(Some(node));
+                }
+                (Write::Set(key, value), None) | (Write::Create(key, value), None) => {
+                    self.metrics.incr(Counter::Sets);
+                    nodes.push(Some(Node::new(key, value, false /*black*/)));
+                }
+                (Write::Delete(_), _) => self.metrics.incr(Counter::Deletes),
+            }
+        }
+        nodes.extend(olds);
+
+        let height = black_height(nodes.len());
+        self.root = build_tree(&mut nodes, height);
+        self.n_count = nodes.len();
+        if self.is_paranoid() {
+            self.check_tree("apply");
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "batch_test.rs"]
+mod batch_test;
@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_apply() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let mut batch = WriteBatch::new();
+    batch.set(20, 200);
+    batch.delete(3);
+    batch.create(10, 100);
+    batch.set(5, 50);
+    batch.delete(10);
+    batch.create(10, 101);
+    batch.delete(100);
+    batch.create(3, 30);
+    assert_eq!(batch.len(), 8);
+    assert_eq!(llrb.apply(batch), Ok(8));
+
+    let refs = vec![
+        (0, 0),
+        (1, 1),
+        (2, 2),
+        (3, 30),
+        (4, 4),
+        (5, 50),
+        (6, 6),
+        (7, 7),
+        (8, 8),
+        (9, 9),
+        (10, 101),
+        (20, 200),
+    ];
+    assert_eq!(llrb.iter().collect::<Vec<(i64, i64)>>(), refs);
+    assert_eq!(llrb.len(), refs.len());
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_apply_atomic() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let mut batch = WriteBatch::new();
+    batch.set(20, 200);
+    batch.delete(1);
+    batch.create(10, 100);
+    batch.create(2, 20);
+    assert_eq!(llrb.apply(batch), Err(Error::OverwriteKey));
+
+    // create after create on same key.
+    let mut batch = WriteBatch::new();
+    batch.create(10, 100);
+    batch.set(11, 110);
+    batch.create(10, 101);
+    assert_eq!(llrb.apply(batch), Err(Error::OverwriteKey));
+
+    let refs: Vec<(i64, i64)> = (0..10).map(|key| (key, key)).collect();
+    assert_eq!(llrb.iter().collect::<Vec<(i64, i64)>>(), refs);
+    assert_eq!(llrb.len(), 10);
+
+    assert_eq!(llrb.apply(WriteBatch::new()), Ok(0));
+}
+
+#[test]
+fn test_apply_random() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for _ in 0..2_000 {
+        let key = rng.gen_range(0, 4_000);
+        match rng.gen_range(0, 8) {
+            0 => llrb.set_with_ttl(key, key, Duration::from_secs(0)),
+            _ => llrb.set(key, key),
+        };
+    }
+
+    // small batches are applied one by one, large ones are merged.
+    for size in [1, 10, 100, 1_000, 5_000].iter() {
+        let mut refs = llrb.clone();
+        let mut batch = WriteBatch::new();
+        for _ in 0..*size {
+            let key = rng.gen_range(0, 4_000);
+            match rng.gen_range(0, 3) {
+                0 => {
+                    batch.set(key, -key);
+                    refs.set(key, -key);
+                }
+                1 => {
+                    batch.delete(key);
+                    refs.delete(&key);
+                }
+                _ => {
+                    let value = rng.gen_range(0, 100);
+                    batch.delete(key);
+                    batch.create(key, value);
+                    refs.delete(&key);
+                    refs.set(key, value);
+                }
+            }
+        }
+        let ops = batch.len();
+        assert_eq!(llrb.apply(batch), Ok(ops));
+        assert!(llrb.validate().is_ok());
+        assert_eq!(llrb.len(), refs.len());
+        assert!(llrb.iter().eq(refs.iter()));
+        assert_eq!(llrb.stats().expired(), refs.stats().expired());
+    }
+    assert!(llrb.stats().expired() > 0);
+}
+
+#[test]
+fn test_apply_bulk() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for _ in 0..200_000 {
+        let key = rng.gen_range(0, 1_000_000);
+        llrb.set(key, key);
+    }
+    let mut refs = llrb.clone();
+
+    let writes: Vec<(i64, bool)> = (0..100_000)
+        .map(|_| (rng.gen_range(0, 1_000_000), rng.gen()))
+        .collect();
+    let mut batch = WriteBatch::new();
+    for (key, set) in writes.iter() {
+        match set {
+            true => batch.set(*key, -key),
+            false => batch.delete(*key),
+        }
+    }
+
+    // a batch touching half the entries is merged, faster than applying
+    // its operations one by one.
+    let start = Instant::now();
+    for (key, set) in writes.into_iter() {
+        match set {
+            true => refs.set(key, -key),
+            false => refs.delete(&key),
+        };
+    }
+    let one_by_one = start.elapsed();
+
+    let start = Instant::now();
+    assert_eq!(llrb.apply(batch), Ok(100_000));
+    let merged = start.elapsed();
+
+    println!("one by one {:?}, merged {:?}", one_by_one, merged);
+    assert!(merged < one_by_one);
+    assert!(llrb.validate().is_ok());
+    assert!(llrb.iter().eq(refs.iter()));
+}
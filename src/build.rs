@@ -0,0 +1,304 @@
+#[cfg(feature = "rayon")]
+use rayon::slice::ParallelSliceMut;
+
+use std::mem;
+
+use crate::aggregate::Aggregate;
+use crate::error::Error;
+use crate::llrb::{Llrb, Node};
+
+// Sub-trees smaller than this are built on the calling thread.
+#[cfg(feature = "rayon")]
+const PAR_THRESHOLD: usize = 16 * 1024;
+
+/// Duplicates enumerates the ways to handle a key that repeats in the
+/// input, while building Llrb instance via [`Llrb::build`] or
+/// `Llrb::par_build`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Duplicates {
+    /// Keep the entry that comes last in the input.
+    LastWins,
+    /// Fail the build with [`Error::DuplicateKey`].
+    Error,
+}
+
+// Entries for a sub-tree, split around its root. A 2-node root holds
+// one entry, a 3-node root holds two entries, the smaller one as its
+// red left child.
+enum Split<'a, T> {
+    Two(Entries<'a, T>, T, Entries<'a, T>),
+    Three(Entries<'a, T>, T, Entries<'a, T>, T, Entries<'a, T>),
+}
+
+type Entries<'a, T> = &'a mut [Option<T>];
+
+// Entry to build a tree from, either a {key, value} pair or a node
+// detached from another tree, that is reused as is.
+pub(crate) trait IntoNode<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn into_node(self) -> Box<Node<K, V, A>>;
+}
+
+impl<K, V, A> IntoNode<K, V, A> for (K, V)
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn into_node(self) -> Box<Node<K, V, A>> {
+        Node::new(self.0, self.1, false /*black*/)
+    }
+}
+
+impl<K, V, A> IntoNode<K, V, A> for Box<Node<K, V, A>>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn into_node(self) -> Box<Node<K, V, A>> {
+        self
+    }
+}
+
+/// Bulk build operations.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Build a new instance of Llrb, identified by `name`, from unsorted
+    /// entries. Entries are sorted and de-duplicated as per `dups`, and
+    /// the tree is constructed bottom-up in O(n), which is faster than
+    /// setting the entries one by one.
+    pub fn build<S, I>(name: S, iter: I, dups: Duplicates) -> Result<Llrb<K, V>, Error<K>>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        // stable sort, preserves the input order of duplicate keys.
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        dedup(&mut entries, dups)?;
+
+        let mut entries: Vec<Option<(K, V)>> = entries.into_iter().map(Some).collect();
+        let height = black_height(entries.len());
+        let mut llrb = Llrb::new(name);
+        llrb.root = build_tree(&mut entries, height);
+        llrb.n_count = entries.len();
+        if llrb.is_paranoid() {
+            llrb.check_tree("build");
+        }
+        Ok(llrb)
+    }
+
+    /// Same as [`Llrb::build`], except that entries are sorted and the
+    /// tree is constructed using rayon's thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_build<S, I>(name: S, iter: I, dups: Duplicates) -> Result<Llrb<K, V>, Error<K>>
+    where
+        K: Send,
+        V: Send,
+        S: AsRef<str>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        // stable sort, preserves the input order of duplicate keys.
+        entries.par_sort_by(|a, b| a.0.cmp(&b.0));
+        dedup(&mut entries, dups)?;
+
+        let mut entries: Vec<Option<(K, V)>> = entries.into_iter().map(Some).collect();
+        let height = black_height(entries.len());
+        let mut llrb = Llrb::new(name);
+        llrb.root = par_build_tree(&mut entries, height);
+        llrb.n_count = entries.len();
+        if llrb.is_paranoid() {
+            llrb.check_tree("par_build");
+        }
+        Ok(llrb)
+    }
+}
+
+// Remove duplicate keys from sorted entries, as per `dups`.
+fn dedup<K, V>(entries: &mut Vec<(K, V)>, dups: Duplicates) -> Result<(), Error<K>>
+where
+    K: Clone + Ord,
+{
+    match dups {
+        Duplicates::LastWins => {
+            // retain the first slot of a run, with the last entry in it.
+            entries.dedup_by(|next, prev| {
+                let ok = next.0 == prev.0;
+                if ok {
+                    mem::swap(next, prev);
+                }
+                ok
+            });
+            Ok(())
+        }
+        Duplicates::Error => match entries.windows(2).find(|w| w[0].0 == w[1].0) {
+            Some(w) => Err(Error::DuplicateKey(w[0].0.clone())),
+            None => Ok(()),
+        },
+    }
+}
+
+// Number of black nodes from root to leaf, for a tree holding n
+// entries. A 2-3 tree of this height holds between 2^h - 1 and 3^h - 1
+// entries.
+pub(crate) fn black_height(n: usize) -> usize {
+    let bits = mem::size_of::<usize>() * 8;
+    bits - ((n + 1).leading_zeros() as usize) - 1
+}
+
+// Maximum number of entries in a 2-3 tree of `height`.
+fn capacity(height: usize) -> usize {
+    3_usize.saturating_pow(height as u32) - 1
+}
+
+fn split<T>(entries: Entries<T>, height: usize) -> Split<T> {
+    let n = entries.len();
+    let max = capacity(height - 1);
+    if (n - 1) <= (2 * max) {
+        let (left, rest) = entries.split_at_mut((n - 1) / 2);
+        let (mid, right) = rest.split_at_mut(1);
+        Split::Two(left, mid[0].take().unwrap(), right)
+    } else {
+        let m = n - 2;
+        let (a, b) = (m / 3, (m - (m / 3)) / 2);
+        let (left, rest) = entries.split_at_mut(a);
+        let (red, rest) = rest.split_at_mut(1);
+        let (mid, rest) = rest.split_at_mut(b);
+        let (black, right) = rest.split_at_mut(1);
+        let red = red[0].take().unwrap();
+        Split::Three(left, red, mid, black[0].take().unwrap(), right)
+    }
+}
+
+// Join sub-trees with their root entries, refer to Split.
+fn join<K, V, A, T>(
+    entry: T,
+    left: Option<Box<Node<K, V, A>>>,
+    right: Option<Box<Node<K, V, A>>>,
+    black: bool,
+) -> Option<Box<Node<K, V, A>>>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+    T: IntoNode<K, V, A>,
+{
+    let mut node = entry.into_node();
+    node.black = black;
+    node.left = left;
+    node.right = right;
+    node.repair();
+    Some(node)
+}
+
+// Rebuild a tree, that might have lost its balance, from its nodes.
+pub(crate) fn rebuild_tree<K, V, A>(node: Option<Box<Node<K, V, A>>>) -> Option<Box<Node<K, V, A>>>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    let mut nodes = drain_tree(node);
+    let height = black_height(nodes.len());
+    build_tree(&mut nodes, height)
+}
+
+// Detach the nodes of a tree, in sort order, to be reused by build_tree.
+pub(crate) fn drain_tree<K, V, A>(
+    node: Option<Box<Node<K, V, A>>>,
+) -> Vec<Option<Box<Node<K, V, A>>>>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    let mut nodes = vec![];
+    let (mut node, mut stack) = (node, vec![]);
+    loop {
+        while let Some(mut nref) = node {
+            node = nref.left.take();
+            stack.push(nref);
+        }
+        let mut nref = match stack.pop() {
+            Some(nref) => nref,
+            None => break,
+        };
+        node = nref.right.take();
+        nodes.push(Some(nref));
+    }
+    nodes
+}
+
+// Build a tree of `height` blacks from sorted entries, bottom-up.
+pub(crate) fn build_tree<K, V, A, T>(
+    entries: Entries<T>,
+    height: usize,
+) -> Option<Box<Node<K, V, A>>>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+    T: IntoNode<K, V, A>,
+{
+    if entries.is_empty() {
+        return None;
+    }
+    match split(entries, height) {
+        Split::Two(left, entry, right) => {
+            let left = build_tree(left, height - 1);
+            let right = build_tree(right, height - 1);
+            join(entry, left, right, true)
+        }
+        Split::Three(left, red, mid, black, right) => {
+            let left = build_tree(left, height - 1);
+            let mid = build_tree(mid, height - 1);
+            let right = build_tree(right, height - 1);
+            join(black, join(red, left, mid, false), right, true)
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn par_build_tree<K, V>(entries: Entries<(K, V)>, height: usize) -> Option<Box<Node<K, V>>>
+where
+    K: Clone + Ord + Send,
+    V: Clone + Send,
+{
+    if entries.len() < PAR_THRESHOLD {
+        return build_tree(entries, height);
+    }
+    match split(entries, height) {
+        Split::Two(left, entry, right) => {
+            let (left, right) = rayon::join(
+                || par_build_tree(left, height - 1),
+                || par_build_tree(right, height - 1),
+            );
+            join(entry, left, right, true)
+        }
+        Split::Three(left, red, mid, black, right) => {
+            let (left, (mid, right)) = rayon::join(
+                || par_build_tree(left, height - 1),
+                || {
+                    rayon::join(
+                        || par_build_tree(mid, height - 1),
+                        || par_build_tree(right, height - 1),
+                    )
+                },
+            );
+            join(black, join(red, left, mid, false), right, true)
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "build_test.rs"]
+mod build_test;
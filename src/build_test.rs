@@ -0,0 +1,82 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_build() {
+    for n in 0..1000 {
+        let entries = (0..n).map(|key| (key, key * 10));
+        let llrb: Llrb<i64, i64> = Llrb::build("test-llrb", entries, Duplicates::Error).unwrap();
+        assert_eq!(llrb.len(), n as usize);
+        let stats = llrb.validate().unwrap();
+        assert_eq!(stats.blacks(), Some(black_height(n as usize)));
+        assert!(is_left_leaning(llrb.root.as_deref()));
+        assert_eq!(
+            llrb.iter().map(|(k, _)| k).collect::<Vec<i64>>(),
+            (0..n).collect::<Vec<i64>>()
+        );
+    }
+}
+
+#[test]
+fn test_build_unsorted() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let entries: Vec<(i64, i64)> = (0..10_000)
+        .map(|_| (rng.gen_range(0, 5_000), rng.gen::<i64>()))
+        .collect();
+    let mut refs: Llrb<i64, i64> = Llrb::new("test-refs");
+    refs.extend(entries.clone());
+
+    let dups = Duplicates::LastWins;
+    let mut llrb = Llrb::build("test-llrb", entries.clone(), dups).unwrap();
+    assert!(llrb.validate().is_ok());
+    assert_eq!(llrb.len(), refs.len());
+    assert!(llrb.iter().eq(refs.iter()));
+
+    // built instance is a regular instance.
+    for (key, _) in entries.iter().take(1000) {
+        llrb.delete(key);
+        refs.delete(key);
+    }
+    assert!(llrb.validate().is_ok());
+    assert!(llrb.iter().eq(refs.iter()));
+
+    let mut entries = entries;
+    entries.push((entries[100].0, 0));
+    let err = Llrb::build("test-llrb", entries, Duplicates::Error).err();
+    assert!(matches!(err, Some(Error::DuplicateKey(_))));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_build() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let entries: Vec<(i64, i64)> = (0..200_000)
+        .map(|_| (rng.gen_range(0, 100_000), rng.gen::<i64>()))
+        .collect();
+    let mut refs: Llrb<i64, i64> = Llrb::new("test-refs");
+    refs.extend(entries.clone());
+
+    let llrb = Llrb::par_build("test-llrb", entries.clone(), Duplicates::LastWins).unwrap();
+    assert!(llrb.validate().is_ok());
+    assert_eq!(llrb.len(), refs.len());
+    assert!(llrb.iter().eq(refs.iter()));
+
+    let err = Llrb::par_build("test-llrb", entries, Duplicates::Error).err();
+    assert!(matches!(err, Some(Error::DuplicateKey(_))));
+}
+
+fn is_left_leaning(node: Option<&Node<i64, i64>>) -> bool {
+    match node {
+        Some(node) if node.right.as_ref().is_some_and(|r| !r.black) => false,
+        Some(node) => is_left_leaning(node.left_deref()) && is_left_leaning(node.right_deref()),
+        None => true,
+    }
+}
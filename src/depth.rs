@@ -1,17 +1,21 @@
-// TODO: test cases for Depth.
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 #[allow(unused_imports)]
 use crate::Llrb;
 
+// Percentiles, in per-mille, reported while exporting depth statistics.
+const PER_MILLE: [usize; 5] = [500, 900, 950, 990, 999];
+
 /// Depth calculates minimum, maximum, average and percentile of leaf-node
 /// depths in the [`Llrb`] tree.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Depth {
     samples: usize,
     min: usize,
     max: usize,
     total: usize,
-    depths: [u64; 256],
+    depths: Vec<u64>, // number of leaf-nodes, indexed by depth.
 }
 
 impl Depth {
@@ -20,35 +24,91 @@ impl Depth {
     }
 
     pub(crate) fn sample(&mut self, depth: usize) {
-        self.samples += 1;
-        self.total += depth;
-        if self.min == 0 || depth < self.min {
+        if self.samples == 0 || depth < self.min {
             self.min = depth
         }
-        if self.max == 0 || depth > self.max {
+        if self.samples == 0 || depth > self.max {
             self.max = depth
         }
+        self.samples += 1;
+        self.total += depth;
+        if depth >= self.depths.len() {
+            self.depths.resize(depth + 1, 0);
+        }
         self.depths[depth] += 1;
     }
 
+    /// Merge depth statistics from `other` into this one, say, to
+    /// summarize depths across several [`Llrb`] instances.
+    pub fn merge(&mut self, other: &Depth) {
+        if other.samples == 0 {
+            return;
+        }
+        if self.samples == 0 || other.min < self.min {
+            self.min = other.min
+        }
+        if self.samples == 0 || other.max > self.max {
+            self.max = other.max
+        }
+        self.samples += other.samples;
+        self.total += other.total;
+        if other.depths.len() > self.depths.len() {
+            self.depths.resize(other.depths.len(), 0);
+        }
+        for (depth, n) in other.depths.iter().enumerate() {
+            self.depths[depth] += n;
+        }
+    }
+
     /// Return number of leaf-nodes sampled in [`Llrb`] instance.
     pub fn samples(&self) -> usize {
         self.samples
     }
 
-    /// Return minimum depth of leaf-node in [`Llrb`] instance.
+    /// Return minimum depth of leaf-node in [`Llrb`] instance. Return 0
+    /// if there are no samples.
     pub fn min(&self) -> usize {
         self.min
     }
 
-    /// Return maximum depth of leaf-node in [`Llrb`] instance.
+    /// Return maximum depth of leaf-node in [`Llrb`] instance. Return 0
+    /// if there are no samples.
     pub fn max(&self) -> usize {
         self.max
     }
 
     /// Return the average depth of leaf-nodes in [`Llrb`] instance.
+    /// Return 0 if there are no samples.
     pub fn mean(&self) -> usize {
-        self.total / self.samples
+        self.total.checked_div(self.samples).unwrap_or(0)
+    }
+
+    /// Return the depth at or below which `percentile` of leaf-nodes
+    /// are found, like 50.0 for median or 99.9. Return None if there are
+    /// no samples, or if percentile is not within 0.0 to 100.0.
+    pub fn percentile(&self, percentile: f64) -> Option<usize> {
+        if self.samples == 0 || !(0.0..=100.0).contains(&percentile) {
+            return None;
+        }
+        let rank = ((percentile * self.samples as f64) / 100.0).ceil() as u64;
+        let rank = rank.max(1);
+        let mut acc = 0_u64;
+        for (depth, samples) in self.depths.iter().enumerate() {
+            acc += samples;
+            if acc >= rank {
+                return Some(depth);
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Return the full distribution of leaf-node depths, as tuple of
+    /// (depth, number of leaf-nodes), for depths that have samples.
+    pub fn distribution(&self) -> Vec<(usize, u64)> {
+        let iter = self.depths.iter().enumerate();
+        iter.filter(|(_, n)| **n > 0)
+            .map(|(d, n)| (d, *n))
+            .collect()
     }
 
     /// Return depth as tuple of percentiles, each tuple provides
@@ -84,30 +144,82 @@ impl Depth {
     }
 
     /// Convert depth statistics to JSON format, useful for plotting.
+    /// Percentiles are keyed by percentile, for 50, 90, 95, 99 and 99.9.
     pub fn json(&self) -> String {
         let ps: Vec<String> = self
-            .percentiles()
+            .to_percentiles()
             .into_iter()
-            .map(|(d, n)| format!("{}: {}", d, n))
+            .map(|(p, d)| format!("\"{}\": {}", p, d))
             .collect();
         let strs = [
-            format!("min: {}", self.min),
-            format!("mean: {}", self.mean()),
-            format!("max: {}", self.max),
-            format!("percentiles: {}", ps.join(", ")),
+            format!("\"samples\": {}", self.samples),
+            format!("\"min\": {}", self.min),
+            format!("\"mean\": {}", self.mean()),
+            format!("\"max\": {}", self.max),
+            format!("\"percentiles\": {{{}}}", ps.join(", ")),
         ];
-        ("{ ".to_string() + strs.join(", ").as_str() + " }").to_string()
+        "{".to_string() + strs.join(", ").as_str() + "}"
+    }
+
+    // Write depth statistics, as a summary in prometheus text format,
+    // with `labels` for each sample.
+    pub(crate) fn prometheus(&self, labels: &str, out: &mut String) {
+        let name = "llrb_leaf_depth";
+        let help = "Depth of leaf-nodes in the tree.";
+        out.push_str(&format!("# HELP {} {}\n", name, help));
+        out.push_str(&format!("# TYPE {} summary\n", name));
+        for (pm, depth) in self.to_per_mille() {
+            let quantile = pm as f64 / 1000.0;
+            let labels = format!("{},quantile=\"{}\"", labels, quantile);
+            out.push_str(&format!("{}{{{}}} {}\n", name, labels, depth));
+        }
+        out.push_str(&format!("{}_sum{{{}}} {}\n", name, labels, self.total));
+        out.push_str(&format!("{}_count{{{}}} {}\n", name, labels, self.samples));
+    }
+
+    // Return (percentile, depth) for exported percentiles.
+    fn to_percentiles(&self) -> Vec<(f64, usize)> {
+        let iter = self.to_per_mille().into_iter();
+        iter.map(|(pm, depth)| (pm as f64 / 10.0, depth)).collect()
+    }
+
+    fn to_per_mille(&self) -> Vec<(usize, usize)> {
+        PER_MILLE
+            .iter()
+            .filter_map(|pm| Some((*pm, self.percentile(*pm as f64 / 10.0)?)))
+            .collect()
     }
 }
 
-impl Default for Depth {
-    fn default() -> Self {
-        Depth {
-            samples: 0,
-            min: 0,
-            max: 0,
-            total: 0,
-            depths: [0; 256],
+#[cfg(feature = "serde")]
+impl Serialize for Depth {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // serialize in the same shape as json().
+        struct Percentiles<'a>(&'a Depth);
+
+        impl<'a> Serialize for Percentiles<'a> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let iter = self.0.to_percentiles().into_iter();
+                serializer.collect_map(iter.map(|(p, d)| (p.to_string(), d)))
+            }
         }
+
+        let mut state = serializer.serialize_struct("Depth", 5)?;
+        state.serialize_field("samples", &self.samples)?;
+        state.serialize_field("min", &self.min)?;
+        state.serialize_field("mean", &self.mean())?;
+        state.serialize_field("max", &self.max)?;
+        state.serialize_field("percentiles", &Percentiles(self))?;
+        state.end()
     }
 }
+
+#[cfg(test)]
+#[path = "depth_test.rs"]
+mod depth_test;
@@ -0,0 +1,101 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_depth_empty() {
+    let depth = Depth::new();
+    assert_eq!(depth.samples(), 0);
+    assert_eq!(depth.min(), 0);
+    assert_eq!(depth.max(), 0);
+    assert_eq!(depth.mean(), 0);
+    assert_eq!(depth.percentile(50.0), None);
+    assert_eq!(depth.percentiles(), vec![]);
+    assert_eq!(depth.distribution(), vec![]);
+}
+
+#[test]
+fn test_depth_sample() {
+    let mut depth = Depth::new();
+    // depths beyond 255.
+    [3, 1000, 3, 4, 300].iter().for_each(|d| depth.sample(*d));
+    assert_eq!(depth.samples(), 5);
+    assert_eq!(depth.min(), 3);
+    assert_eq!(depth.max(), 1000);
+    assert_eq!(depth.mean(), 262);
+    assert_eq!(
+        depth.distribution(),
+        vec![(3, 2), (4, 1), (300, 1), (1000, 1)]
+    );
+    assert_eq!(depth.percentile(0.0), Some(3));
+    assert_eq!(depth.percentile(40.0), Some(3));
+    assert_eq!(depth.percentile(50.0), Some(4));
+    assert_eq!(depth.percentile(80.0), Some(300));
+    assert_eq!(depth.percentile(99.9), Some(1000));
+    assert_eq!(depth.percentile(100.0), Some(1000));
+    assert_eq!(depth.percentile(100.1), None);
+    assert_eq!(depth.percentile(-1.0), None);
+    assert_eq!(depth.percentile(f64::NAN), None);
+
+    // depth zero, for a tree with single leaf.
+    let mut depth = Depth::new();
+    depth.sample(0);
+    assert_eq!((depth.min(), depth.max()), (0, 0));
+    assert_eq!(depth.percentile(50.0), Some(0));
+}
+
+#[test]
+fn test_depth_json() {
+    let depth = Depth::new();
+    let value: serde_json::Value = serde_json::from_str(&depth.json()).unwrap();
+    assert_eq!(value["samples"], 0);
+    assert_eq!(value["mean"], 0);
+    assert_eq!(value["percentiles"], serde_json::json!({}));
+
+    let mut depth = Depth::new();
+    (1..=1000).for_each(|d| depth.sample(d));
+    let value: serde_json::Value = serde_json::from_str(&depth.json()).unwrap();
+    assert_eq!(value["samples"], 1000);
+    assert_eq!(value["min"], 1);
+    assert_eq!(value["mean"], 500);
+    assert_eq!(value["max"], 1000);
+    let percentiles = serde_json::json!({
+        "50": 500, "90": 900, "95": 950, "99": 990, "99.9": 999,
+    });
+    assert_eq!(value["percentiles"], percentiles);
+    #[cfg(feature = "serde")]
+    assert_eq!(serde_json::to_value(&depth).unwrap(), value);
+}
+
+#[test]
+fn test_depth_merge() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed:{}", seed);
+
+    let (mut a, mut b, mut all) = (Depth::new(), Depth::new(), Depth::new());
+    for _ in 0..1000 {
+        let d = rng.gen_range(1, 100);
+        a.sample(d);
+        all.sample(d);
+        let d = rng.gen_range(50, 500);
+        b.sample(d);
+        all.sample(d);
+    }
+
+    let mut merged = Depth::new();
+    merged.merge(&Depth::new());
+    assert_eq!(merged.samples(), 0);
+    merged.merge(&a);
+    merged.merge(&b);
+    assert_eq!(merged.samples(), all.samples());
+    assert_eq!(merged.min(), all.min());
+    assert_eq!(merged.max(), all.max());
+    assert_eq!(merged.mean(), all.mean());
+    assert_eq!(merged.distribution(), all.distribution());
+    for p in [0.0, 50.0, 90.0, 99.0, 99.9, 100.0].iter() {
+        assert_eq!(merged.percentile(*p), all.percentile(*p));
+    }
+    assert_eq!(merged.percentiles(), all.percentiles());
+}
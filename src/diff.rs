@@ -0,0 +1,89 @@
+use std::{cmp::Ordering, iter::Peekable, ptr};
+
+use crate::llrb::{Iter, Llrb};
+
+/// Change enumerates the difference in a single entry between two Llrb
+/// instances, refer to [`Llrb::diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<K, V> {
+    /// Entry is present only in the other instance.
+    Added(K, V),
+    /// Entry is present only in this instance.
+    Removed(K, V),
+    /// Entry is present in both instances, with different values. The
+    /// value in this instance followed by the value in other instance.
+    Changed(K, V, V),
+}
+
+/// Diff operation on Llrb instances.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone + PartialEq,
+{
+    /// Return an iterator over the changes, in sort order, that shall
+    /// turn this instance into `other`. Both trees are walked in full,
+    /// in a single merge pass, costing O(n + m) for n and m entries.
+    /// Diffing an instance against itself returns right away.
+    pub fn diff<'a>(&'a self, other: &'a Llrb<K, V>) -> Diff<'a, K, V> {
+        let (this, other) = if ptr::eq(self, other) {
+            (None, None)
+        } else {
+            (Some(self.iter().peekable()), Some(other.iter().peekable()))
+        };
+        Diff { this, other }
+    }
+}
+
+pub struct Diff<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + PartialEq,
+{
+    this: Option<Peekable<Iter<'a, K, V>>>,
+    other: Option<Peekable<Iter<'a, K, V>>>,
+}
+
+impl<'a, K, V> Iterator for Diff<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone + PartialEq,
+{
+    type Item = Change<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (this, other) = match (self.this.as_mut(), self.other.as_mut()) {
+            (Some(this), Some(other)) => (this, other),
+            _ => return None,
+        };
+        loop {
+            let cmp = match (this.peek(), other.peek()) {
+                (Some((k1, _)), Some((k2, _))) => k1.cmp(k2),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break None,
+            };
+            match cmp {
+                Ordering::Less => {
+                    let (key, value) = this.next().unwrap();
+                    break Some(Change::Removed(key, value));
+                }
+                Ordering::Greater => {
+                    let (key, value) = other.next().unwrap();
+                    break Some(Change::Added(key, value));
+                }
+                Ordering::Equal => {
+                    let (key, old) = this.next().unwrap();
+                    let (_, new) = other.next().unwrap();
+                    if old != new {
+                        break Some(Change::Changed(key, old, new));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "diff_test.rs"]
+mod diff_test;
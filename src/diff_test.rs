@@ -0,0 +1,71 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_diff() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    assert_eq!(llrb.diff(&llrb.clone()).count(), 0);
+
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+    let snapshot = llrb.clone();
+    assert_eq!(llrb.diff(&llrb).count(), 0);
+    assert_eq!(llrb.diff(&snapshot).count(), 0);
+
+    llrb.delete(&0);
+    llrb.delete(&5);
+    llrb.set(3, 30);
+    llrb.set(3, 3); // back to the original value.
+    llrb.set(7, 70);
+    llrb.set(10, 10);
+    llrb.set(-1, -1);
+
+    let changes: Vec<Change<i64, i64>> = snapshot.diff(&llrb).collect();
+    let refs = vec![
+        Change::Added(-1, -1),
+        Change::Removed(0, 0),
+        Change::Removed(5, 5),
+        Change::Changed(7, 7, 70),
+        Change::Added(10, 10),
+    ];
+    assert_eq!(changes, refs);
+
+    let changes: Vec<Change<i64, i64>> = llrb.diff(&Llrb::new("empty")).collect();
+    assert_eq!(changes.len(), llrb.len());
+    assert!(changes.iter().all(|c| matches!(c, Change::Removed(_, _))));
+}
+
+#[test]
+fn test_diff_random() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10_000).for_each(|_| {
+        llrb.set(rng.gen_range(0, 20_000), rng.gen_range(0, 4));
+    });
+    let snapshot = llrb.clone();
+    (0..5_000).for_each(|_| {
+        let key = rng.gen_range(0, 20_000);
+        match rng.gen_range(0, 2) {
+            0 => llrb.set(key, rng.gen_range(0, 4)),
+            _ => llrb.delete(&key),
+        };
+    });
+
+    // apply the changes on snapshot and compare.
+    let mut target = snapshot.clone();
+    for change in snapshot.diff(&llrb) {
+        match change {
+            Change::Added(key, value) => assert!(target.set(key, value).is_none()),
+            Change::Removed(key, value) => assert_eq!(target.delete(&key), Some(value)),
+            Change::Changed(key, old, new) => {
+                assert_ne!(old, new);
+                assert_eq!(target.set(key, new), Some(old));
+            }
+        }
+    }
+    assert!(target.iter().eq(llrb.iter()));
+}
@@ -1,3 +1,6 @@
 /// Can be used while indexing keys without values, like ``Llrb<K, Empty>``.
+/// Refer to [`LlrbSet`] for a dedicated wrapper.
+///
+/// [`LlrbSet`]: crate::LlrbSet
 #[derive(Clone, Eq, PartialEq, PartialOrd, Ord)]
 pub struct Empty {}
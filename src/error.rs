@@ -7,11 +7,53 @@ where
 {
     /// Fatal case, breaking one of the two LLRB rules.
     ConsecutiveReds,
-    /// Fatal case, breaking one of the two LLRB rules. The String
-    /// component of this variant can be used for debugging.
-    UnbalancedBlacks(String),
-    /// Fatal case, index entries are not in sort-order.
+    /// Fatal case, breaking one of the two LLRB rules. Number of blacks
+    /// under the left child and the right child of a node.
+    UnbalancedBlacks { left: usize, right: usize },
+    /// Fatal case, a red link leaning right.
+    RedRightLink,
+    /// Fatal case, root node is red.
+    RedRoot,
+    /// Fatal case, index entries are not in sort-order. Entry that sorts
+    /// first in the tree, followed by the entry that sorts next.
     SortError(K, K),
+    /// Fatal case, number of entries counted by the index does not
+    /// match the number of nodes in the tree.
+    CountMismatch { count: usize, nodes: usize },
     /// Returned by create() API when key is already present.
     OverwriteKey,
+    /// Returned while building an index from input that has the same
+    /// key more than once, refer to [`Duplicates::Error`].
+    ///
+    /// [`Duplicates::Error`]: crate::Duplicates::Error
+    DuplicateKey(K),
+    /// Returned while draining changes after `seqno`, when the change log
+    /// no longer holds them. Oldest change held in the log is `oldest`.
+    ChangesDropped { seqno: u64, oldest: u64 },
+    /// Returned while loading a tree from its [`Shape`], when links do
+    /// not form a tree. Offending node is at offset `node`.
+    ///
+    /// [`Shape`]: crate::Shape
+    InvalidShape { node: usize },
+}
+
+impl<K> Error<K>
+where
+    K: Clone + Ord,
+{
+    // Describe the invariant broken, for fatal cases.
+    pub(crate) fn to_invariant(&self) -> &'static str {
+        match self {
+            Error::ConsecutiveReds => "consecutive reds",
+            Error::UnbalancedBlacks { .. } => "unbalanced blacks",
+            Error::RedRightLink => "red right link",
+            Error::RedRoot => "red root",
+            Error::SortError(_, _) => "keys out of sort order",
+            Error::CountMismatch { .. } => "count mismatch",
+            Error::OverwriteKey => "overwrite key",
+            Error::DuplicateKey(_) => "duplicate key",
+            Error::ChangesDropped { .. } => "changes dropped",
+            Error::InvalidShape { .. } => "invalid shape",
+        }
+    }
 }
@@ -0,0 +1,378 @@
+use std::{
+    convert::TryFrom,
+    sync::{Mutex, MutexGuard},
+};
+
+/// Capacity limit for a bounded [`Llrb`](crate::Llrb) instance, refer to
+/// [`Llrb::new_bounded`](crate::Llrb::new_bounded).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Capacity {
+    /// Bound the index by number of entries.
+    Entries(usize),
+    /// Bound the index by memory footprint, in bytes. Each entry is
+    /// accounted as its node-size plus its footprint, refer to
+    /// [`Llrb::set_footprint`](crate::Llrb::set_footprint).
+    Bytes(usize),
+}
+
+/// Eviction policy for a bounded [`Llrb`](crate::Llrb) instance. Victims
+/// are picked one at a time until the index fits within its [`Capacity`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Eviction {
+    /// Evict the least recently used entry. Both reads, via
+    /// [`Llrb::get`](crate::Llrb::get), and writes count as use.
+    Lru,
+    /// Evict the entry that was inserted first. Updating an entry's value
+    /// does not change its age.
+    Oldest,
+    /// Evict the entry with the smallest key.
+    Smallest,
+    /// Evict the entry with the largest key.
+    Largest,
+}
+
+// Slot of a node that is not tracked in the access order.
+pub(crate) const NO_SLOT: u32 = u32::MAX;
+
+// Link in the access order, to its neighbours, by slot.
+#[derive(Clone)]
+struct Link<K> {
+    key: Option<K>,
+    stamp: u64,
+    prev: u32,
+    next: u32,
+}
+
+/// Position of an entry in access order, to restore it after the entry
+/// is written, refer to [`Evictor::reposition`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Position {
+    stamp: u64,
+    prev: u32,
+}
+
+// Access order, maintained only for Lru and Oldest policies, as a doubly
+// linked list of keys, least recent first. Links are held in a slab, and
+// every node in the tree remembers the slot of its link, so that an
+// access is re-linked in constant time. Every link is stamped from a
+// monotonic clock, as it is linked at the tail, hence stamps are in
+// sort order from head to tail. Vacant slots are chained via `next`,
+// starting from `free`.
+#[derive(Clone)]
+struct Order<K> {
+    links: Vec<Link<K>>,
+    clock: u64,
+    head: u32,
+    tail: u32,
+    free: u32,
+}
+
+impl<K> Order<K>
+where
+    K: Clone,
+{
+    fn new() -> Order<K> {
+        Order {
+            links: vec![],
+            clock: 0,
+            head: NO_SLOT,
+            tail: NO_SLOT,
+            free: NO_SLOT,
+        }
+    }
+
+    // slot for the next key to be tracked.
+    fn vacant(&self) -> u32 {
+        if self.free != NO_SLOT {
+            return self.free;
+        }
+        match u32::try_from(self.links.len()) {
+            Ok(slot) if slot != NO_SLOT => slot,
+            _ => panic!("access order overflow, too many entries"),
+        }
+    }
+
+    // track key at `slot`, as the most recent.
+    fn occupy(&mut self, slot: u32, key: K) {
+        if slot == self.free {
+            self.free = self.links[slot as usize].next;
+            self.links[slot as usize].key = Some(key);
+        } else {
+            let (stamp, prev, next) = (0, NO_SLOT, NO_SLOT);
+            self.links.push(Link {
+                key: Some(key),
+                stamp,
+                prev,
+                next,
+            });
+        }
+        self.push_back(slot);
+    }
+
+    fn touch(&mut self, slot: u32) {
+        if slot != self.tail {
+            self.unlink(slot);
+            self.push_back(slot);
+        }
+    }
+
+    fn vacate(&mut self, slot: u32) {
+        self.unlink(slot);
+        let link = &mut self.links[slot as usize];
+        link.key = None;
+        link.next = self.free;
+        self.free = slot;
+    }
+
+    fn position(&self, slot: u32) -> Position {
+        let link = &self.links[slot as usize];
+        Position {
+            stamp: link.stamp,
+            prev: link.prev,
+        }
+    }
+
+    // move `slot` back to a prior position, after the last link stamped
+    // before it. Its prior neighbour is tried first, failing which links
+    // are walked from the tail.
+    fn reposition(&mut self, slot: u32, position: Position) {
+        self.unlink(slot);
+        let stamp = position.stamp;
+        let is_before = |prev: u32| -> bool {
+            match self.links.get(prev as usize) {
+                Some(link) => link.key.is_some() && link.stamp < stamp,
+                None => prev == NO_SLOT,
+            }
+        };
+        let is_after = |prev: u32| -> bool {
+            let next = match prev {
+                NO_SLOT => self.head,
+                prev => self.links[prev as usize].next,
+            };
+            next == NO_SLOT || self.links[next as usize].stamp > stamp
+        };
+        let prev = if is_before(position.prev) && is_after(position.prev) {
+            position.prev
+        } else {
+            let mut prev = self.tail;
+            while prev != NO_SLOT && self.links[prev as usize].stamp > stamp {
+                prev = self.links[prev as usize].prev;
+            }
+            prev
+        };
+        self.link_after(prev, slot);
+        self.links[slot as usize].stamp = stamp;
+    }
+
+    fn push_back(&mut self, slot: u32) {
+        self.clock += 1;
+        self.links[slot as usize].stamp = self.clock;
+        self.link_after(self.tail, slot);
+    }
+
+    fn link_after(&mut self, prev: u32, slot: u32) {
+        let next = match prev {
+            NO_SLOT => self.head,
+            prev => self.links[prev as usize].next,
+        };
+        let link = &mut self.links[slot as usize];
+        link.prev = prev;
+        link.next = next;
+        match prev {
+            NO_SLOT => self.head = slot,
+            prev => self.links[prev as usize].next = slot,
+        }
+        match next {
+            NO_SLOT => self.tail = slot,
+            next => self.links[next as usize].prev = slot,
+        }
+    }
+
+    fn unlink(&mut self, slot: u32) {
+        let Link { prev, next, .. } = self.links[slot as usize];
+        match prev {
+            NO_SLOT => self.head = next,
+            prev => self.links[prev as usize].next = next,
+        }
+        match next {
+            NO_SLOT => self.tail = prev,
+            next => self.links[next as usize].prev = prev,
+        }
+    }
+}
+
+/// Evictor book-keeps the memory footprint and access order of entries
+/// in a bounded [`Llrb`](crate::Llrb) instance.
+pub(crate) struct Evictor<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    capacity: Capacity,
+    policy: Eviction,
+    node_size: usize,
+    footprint: Option<fn(&K, &V) -> usize>,
+    bytes: usize,
+    // access order is updated by read operations, via shared reference.
+    order: Mutex<Order<K>>,
+}
+
+impl<K, V> Clone for Evictor<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Evictor {
+            capacity: self.capacity,
+            policy: self.policy,
+            node_size: self.node_size,
+            footprint: self.footprint,
+            bytes: self.bytes,
+            order: Mutex::new(self.order().clone()),
+        }
+    }
+}
+
+impl<K, V> Evictor<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    pub(crate) fn new(capacity: Capacity, policy: Eviction, node_size: usize) -> Self {
+        Evictor {
+            capacity,
+            policy,
+            node_size,
+            footprint: None,
+            bytes: 0,
+            order: Mutex::new(Order::new()),
+        }
+    }
+
+    // a panic while holding the lock shall not render the index unusable,
+    // the access order is only a hint for picking the victim.
+    fn order(&self) -> MutexGuard<'_, Order<K>> {
+        match self.order.lock() {
+            Ok(order) => order,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    #[inline]
+    fn is_ordered(&self) -> bool {
+        matches!(self.policy, Eviction::Lru | Eviction::Oldest)
+    }
+
+    #[inline]
+    pub(crate) fn policy(&self) -> Eviction {
+        self.policy
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> Capacity {
+        self.capacity
+    }
+
+    #[inline]
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Return the number of bytes accounted for {key, value} entry.
+    #[inline]
+    pub(crate) fn cost(&self, key: &K, value: &V) -> usize {
+        self.node_size + self.footprint.map_or(0, |f| f(key, value))
+    }
+
+    /// Return the slot, in access order, for the next entry to be
+    /// inserted, refer to [`Evictor::inserted`].
+    pub(crate) fn vacant(&self) -> u32 {
+        match self.is_ordered() {
+            true => self.order().vacant(),
+            false => NO_SLOT,
+        }
+    }
+
+    pub(crate) fn set_footprint(&mut self, footprint: fn(&K, &V) -> usize) {
+        self.footprint = Some(footprint)
+    }
+
+    pub(crate) fn set_bytes(&mut self, bytes: usize) {
+        self.bytes = bytes
+    }
+
+    pub(crate) fn is_over(&self, entries: usize) -> bool {
+        match self.capacity {
+            Capacity::Entries(limit) => entries > limit,
+            Capacity::Bytes(limit) => self.bytes > limit,
+        }
+    }
+
+    /// Track a new entry, at the slot handed out by [`Evictor::vacant`].
+    /// Key is taken by value, it shall be cloned before the tree is
+    /// touched.
+    pub(crate) fn inserted(&mut self, slot: u32, key: K, cost: usize) {
+        self.bytes += cost;
+        if self.is_ordered() {
+            self.order().occupy(slot, key);
+        }
+    }
+
+    pub(crate) fn updated(&mut self, slot: u32, key: &K, old_value: &V, cost: usize) {
+        if self.policy == Eviction::Lru {
+            self.order().touch(slot);
+        }
+        let old_cost = self.cost(key, old_value);
+        self.bytes = (self.bytes + cost).saturating_sub(old_cost);
+    }
+
+    /// Return the position of an entry in access order, for Lru and
+    /// Oldest policies.
+    pub(crate) fn position(&self, slot: u32) -> Option<Position> {
+        match self.is_ordered() {
+            true => Some(self.order().position(slot)),
+            false => None,
+        }
+    }
+
+    /// Move an entry back to a prior `position` in access order.
+    pub(crate) fn reposition(&mut self, slot: u32, position: Position) {
+        self.order().reposition(slot, position)
+    }
+
+    pub(crate) fn accessed(&self, slot: u32) {
+        if self.policy == Eviction::Lru {
+            self.order().touch(slot);
+        }
+    }
+
+    /// Untrack an entry, its slot is vacated before its cost is computed,
+    /// so that a panicking footprint shall not leave behind a victim
+    /// without its entry.
+    pub(crate) fn removed(&mut self, slot: u32, key: &K, value: &V) {
+        if self.is_ordered() {
+            self.order().vacate(slot);
+        }
+        self.bytes = self.bytes.saturating_sub(self.cost(key, value));
+    }
+
+    pub(crate) fn cleared(&mut self) {
+        self.bytes = 0;
+        *self.order() = Order::new();
+    }
+
+    /// Return the victim as per Lru or Oldest policy. For other policies
+    /// victim is picked from the tree.
+    pub(crate) fn victim(&self) -> Option<K> {
+        let order = self.order();
+        match order.head {
+            NO_SLOT => None,
+            head => order.links[head as usize].key.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "evict_test.rs"]
+mod evict_test;
@@ -0,0 +1,146 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+use crate::Llrb;
+
+#[test]
+fn test_evict_lru() {
+    let mut llrb: Llrb<i64, i64> =
+        Llrb::new_bounded("test-llrb", Capacity::Entries(3), Eviction::Lru);
+    assert_eq!(llrb.capacity(), Some((Capacity::Entries(3), Eviction::Lru)));
+
+    assert!(llrb.create(1, 10).is_ok());
+    assert!(llrb.create(2, 20).is_ok());
+    assert!(llrb.create(3, 30).is_ok());
+    assert_eq!(llrb.get(&1), Some(10)); // 1 is now most recent.
+    assert_eq!(llrb.set(3, 31), Some(30)); // 3 is now most recent.
+    assert!(llrb.set(4, 40).is_none());
+
+    assert_eq!(llrb.len(), 3);
+    assert_eq!(llrb.drain_evicted(), vec![(2, 20)]);
+    assert!(llrb.drain_evicted().is_empty());
+
+    assert!(llrb.set(5, 50).is_none());
+    assert_eq!(llrb.drain_evicted(), vec![(1, 10)]);
+    assert!(llrb.delete(&3).is_some());
+    assert!(llrb.set(6, 60).is_none());
+    assert!(llrb.drain_evicted().is_empty());
+
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![4, 5, 6]);
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_evict_lru_random() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> =
+        Llrb::new_bounded("test-llrb", Capacity::Entries(10), Eviction::Lru);
+    // reference access order, least recent first.
+    let mut order: Vec<i64> = vec![];
+    for _ in 0..10_000 {
+        let key = rng.gen_range(0, 30);
+        let pos = order.iter().position(|k| *k == key);
+        match rng.gen_range(0, 3) {
+            0 => {
+                llrb.set(key, key);
+                pos.map(|pos| order.remove(pos));
+                order.push(key);
+                if order.len() > 10 {
+                    let victim = order.remove(0);
+                    assert_eq!(llrb.drain_evicted(), vec![(victim, victim)]);
+                }
+            }
+            1 => {
+                assert_eq!(llrb.get(&key), pos.map(|_| key));
+                if let Some(pos) = pos {
+                    order.remove(pos);
+                    order.push(key);
+                }
+            }
+            _ => {
+                assert_eq!(llrb.delete(&key), pos.map(|_| key));
+                pos.map(|pos| order.remove(pos));
+            }
+        }
+        assert!(llrb.drain_evicted().is_empty());
+    }
+    let mut keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    order.sort_unstable();
+    keys.sort_unstable();
+    assert_eq!(keys, order);
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_evict_oldest() {
+    let mut llrb: Llrb<i64, i64> =
+        Llrb::new_bounded("test-llrb", Capacity::Entries(2), Eviction::Oldest);
+
+    assert!(llrb.set(1, 10).is_none());
+    assert!(llrb.set(2, 20).is_none());
+    assert_eq!(llrb.get(&1), Some(10));
+    assert_eq!(llrb.set(1, 11), Some(10));
+    assert!(llrb.set(3, 30).is_none());
+
+    assert_eq!(llrb.drain_evicted(), vec![(1, 11)]);
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![2, 3]);
+}
+
+#[test]
+fn test_evict_smallest_largest() {
+    let mut llrb: Llrb<i64, i64> =
+        Llrb::new_bounded("test-llrb", Capacity::Entries(5), Eviction::Smallest);
+    (0..10).for_each(|key| assert!(llrb.set(key, key * 10).is_none()));
+    let evicted: Vec<i64> = llrb.drain_evicted().into_iter().map(|(k, _)| k).collect();
+    assert_eq!(evicted, vec![0, 1, 2, 3, 4]);
+    assert_eq!(llrb.len(), 5);
+    assert!(llrb.validate().is_ok());
+
+    let mut llrb: Llrb<i64, i64> =
+        Llrb::new_bounded("test-llrb", Capacity::Entries(5), Eviction::Largest);
+    (0..10).for_each(|key| assert!(llrb.set(key, key * 10).is_none()));
+    let evicted: Vec<i64> = llrb.drain_evicted().into_iter().map(|(k, _)| k).collect();
+    assert_eq!(evicted, vec![5, 6, 7, 8, 9]);
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_evict_bytes() {
+    let capacity = Capacity::Bytes(1024);
+    let mut llrb: Llrb<i64, Vec<u8>> = Llrb::new_bounded("test-llrb", capacity, Eviction::Oldest);
+    let node_size = llrb.stats().node_size();
+    llrb.set_footprint(|_, value| value.len());
+
+    for key in 0..10 {
+        assert!(llrb.set(key, vec![0; 100]).is_none());
+        assert!(llrb.footprint().unwrap() <= 1024);
+    }
+    let n = 1024 / (node_size + 100);
+    assert_eq!(llrb.len(), n);
+    assert_eq!(llrb.footprint(), Some(n * (node_size + 100)));
+    assert_eq!(llrb.drain_evicted().len(), 10 - n);
+
+    // growing an entry's value shall evict older entries.
+    let key = 9;
+    assert!(llrb.set(key, vec![0; 600]).is_some());
+    assert!(llrb.footprint().unwrap() <= 1024);
+    assert_eq!(llrb.get(&key).map(|v| v.len()), Some(600));
+
+    // re-accounting entries shall evict entries as well, oldest first,
+    // until the index fits within its capacity.
+    llrb.drain_evicted();
+    llrb.set_footprint(|_, value| value.len() * 2);
+    assert!(llrb.footprint().unwrap() <= 1024);
+    let evicted = llrb.drain_evicted();
+    assert!(!evicted.is_empty());
+    assert_eq!(evicted.last().map(|(k, _)| *k), Some(key));
+    assert_eq!(llrb.len(), 0);
+    assert_eq!(llrb.footprint(), Some(0));
+}
@@ -0,0 +1,201 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::aggregate::Aggregate;
+use crate::llrb::{build_iter, find_start, Fragment, IFlag, Llrb};
+
+/// Explain describes how a lookup walked the [`Llrb`] tree, useful to
+/// diagnose pathological key types or skewed trees. Refer to
+/// [`Llrb::explain_get`] and [`Llrb::explain_range`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explain<K> {
+    visits: Vec<Visit<K>>,
+    comparisons: usize,
+    fragments: usize,
+    entries: usize,
+}
+
+impl<K> Explain<K>
+where
+    K: Clone,
+{
+    fn new() -> Explain<K> {
+        Explain {
+            visits: vec![],
+            comparisons: 0,
+            fragments: 0,
+            entries: 0,
+        }
+    }
+
+    /// Return nodes visited, in the order they were visited.
+    pub fn visits(&self) -> &[Visit<K>] {
+        &self.visits
+    }
+
+    /// Return number of key comparisons made.
+    pub fn comparisons(&self) -> usize {
+        self.comparisons
+    }
+
+    /// Return the maximum depth reached, root being at depth 1, same as
+    /// [`crate::Depth`]. Return None if no node was visited.
+    pub fn depth(&self) -> Option<usize> {
+        self.visits.iter().map(|visit| visit.depth).max()
+    }
+
+    /// Return number of iterator fragments pushed, while scanning a
+    /// range. Zero for get.
+    pub fn fragments(&self) -> usize {
+        self.fragments
+    }
+
+    /// Return number of entries found.
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    // Record fragments pushed into `paths` from offset `off`, the path
+    // from root to a fragment is held by `paths` preceding it.
+    fn pushed<V, A>(&mut self, paths: &[Fragment<K, V, A>], off: usize)
+    where
+        K: Ord,
+        V: Clone,
+        A: Aggregate<K, V>,
+    {
+        for (i, fragment) in paths.iter().enumerate().skip(off) {
+            let depth = i + 1;
+            let (key, black) = (fragment.nref.key.clone(), fragment.nref.black);
+            self.visits.push(Visit { key, depth, black });
+            self.fragments += 1;
+        }
+    }
+}
+
+/// Node visited while walking [`Llrb`] tree, refer to [`Explain`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visit<K> {
+    key: K,
+    depth: usize,
+    black: bool,
+}
+
+impl<K> Visit<K> {
+    /// Return the node's key.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Return the node's depth, root being at depth 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Return whether the node is black, else it is red.
+    pub fn is_black(&self) -> bool {
+        self.black
+    }
+}
+
+/// Explain operations on Llrb instance.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Explain how [`Llrb::get`] walks the tree for `key`.
+    pub fn explain_get<Q>(&self, key: &Q) -> Explain<K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut explain = Explain::new();
+        let live = self.live();
+        let (mut node, mut depth) = (self.root.as_deref(), 1);
+        while let Some(nref) = node {
+            let (key_n, black) = (nref.key.clone(), nref.black);
+            explain.visits.push(Visit {
+                key: key_n,
+                depth,
+                black,
+            });
+            explain.comparisons += 1;
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => nref.right_deref(),
+                Ordering::Greater => nref.left_deref(),
+                Ordering::Equal => {
+                    let ok = live.as_ref().is_none_or(|l| l.is_live(&nref.key));
+                    explain.entries = if ok { 1 } else { 0 };
+                    None
+                }
+            };
+            depth += 1;
+        }
+        explain
+    }
+
+    /// Explain how [`Llrb::range`] walks the tree, from low to high.
+    /// Range is scanned in full, costing as much as the scan itself.
+    pub fn explain_range<Q, R>(&self, range: R) -> Explain<K>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut explain = Explain::new();
+        let live = self.live();
+        let root = self.root.as_deref();
+        let mut paths = vec![];
+        match range.start_bound() {
+            Bound::Unbounded => build_iter(IFlag::Left, root, &mut paths),
+            Bound::Included(low) => find_start(root, low, true, &mut paths),
+            Bound::Excluded(low) => find_start(root, low, false, &mut paths),
+        };
+        explain.pushed(&paths, 0);
+        if let Bound::Included(_) | Bound::Excluded(_) = range.start_bound() {
+            explain.comparisons += paths.len();
+        }
+
+        // same as iterating on Range, until an entry beyond high.
+        while let Some(path) = paths.last_mut() {
+            match (path.flag, path.nref) {
+                (IFlag::Left, nref) => {
+                    path.flag = IFlag::Center;
+                    let ok = match range.end_bound() {
+                        Bound::Included(high) => nref.key.borrow().le(high),
+                        Bound::Excluded(high) => nref.key.borrow().lt(high),
+                        Bound::Unbounded => true,
+                    };
+                    if let Bound::Included(_) | Bound::Excluded(_) = range.end_bound() {
+                        explain.comparisons += 1;
+                    }
+                    match ok {
+                        true if live.as_ref().is_none_or(|l| l.is_live(&nref.key)) => {
+                            explain.entries += 1
+                        }
+                        true => (),
+                        false => break,
+                    }
+                }
+                (IFlag::Center, nref) => {
+                    path.flag = IFlag::Right;
+                    let off = paths.len();
+                    build_iter(IFlag::Left, nref.right_deref(), &mut paths);
+                    explain.pushed(&paths, off);
+                }
+                (_, _) => {
+                    paths.pop();
+                }
+            }
+        }
+        explain
+    }
+}
+
+#[cfg(test)]
+#[path = "explain_test.rs"]
+mod explain_test;
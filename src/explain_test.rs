@@ -0,0 +1,109 @@
+use std::{ops::Bound, time::Duration};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_explain_get() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-explain-get");
+    let explain = llrb.explain_get(&10);
+    assert_eq!(explain.visits(), &[]);
+    assert_eq!(explain.depth(), None);
+    assert_eq!(explain.entries(), 0);
+
+    for _ in 0..1000 {
+        let key = rng.gen_range(0, 1000);
+        llrb.set(key, key);
+    }
+    let max = llrb.validate().unwrap().depths().unwrap().max();
+
+    for _ in 0..1000 {
+        let key = rng.gen_range(-10, 1010);
+        let explain = llrb.explain_get(&key);
+        let n = explain.visits().len();
+        assert_eq!(explain.comparisons(), n);
+        assert_eq!(explain.fragments(), 0);
+        assert_eq!(explain.depth(), Some(n));
+        assert!(n <= max, "{} {}", n, max);
+        let found = llrb.get(&key).is_some();
+        assert_eq!(explain.entries(), if found { 1 } else { 0 });
+
+        // path walked, from the root, shall lead to key.
+        let keys: Vec<i64> = explain.visits().iter().map(|v| *v.key()).collect();
+        assert_eq!(keys[0], llrb.root.as_ref().unwrap().key);
+        assert!(explain.visits()[0].is_black());
+        for (i, visit) in explain.visits().iter().enumerate() {
+            assert_eq!(visit.depth(), i + 1);
+            assert_eq!(llrb.explain_get(visit.key()).visits()[i], *visit);
+        }
+        assert_eq!(keys[n - 1] == key, found);
+    }
+
+    llrb.set_with_ttl(2000, 2000, Duration::from_secs(0));
+    let explain = llrb.explain_get(&2000);
+    assert_eq!(explain.visits().last().map(|v| *v.key()), Some(2000));
+    assert_eq!(explain.entries(), 0);
+}
+
+#[test]
+fn test_explain_range() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-explain-range");
+    let explain = llrb.explain_range::<i64, _>(..);
+    assert_eq!(explain.visits(), &[]);
+    assert_eq!(explain.fragments(), 0);
+
+    for _ in 0..1000 {
+        let key = rng.gen_range(0, 1000);
+        llrb.set(key, key);
+    }
+    let max = llrb.validate().unwrap().depths().unwrap().max();
+
+    // full table scan visits every node, without comparisons.
+    let explain = llrb.explain_range::<i64, _>(..);
+    assert_eq!(explain.entries(), llrb.len());
+    assert_eq!(explain.visits().len(), llrb.len());
+    assert_eq!(explain.fragments(), llrb.len());
+    assert_eq!(explain.comparisons(), 0);
+    assert_eq!(explain.depth(), Some(max));
+
+    for _ in 0..1000 {
+        let (a, b) = (rng.gen_range(-10, 1010), rng.gen_range(-10, 1010));
+        let (low, high) = (a.min(b), a.max(b));
+        let low = match rng.gen_range(0, 3) {
+            0 => Bound::Included(low),
+            1 => Bound::Excluded(low),
+            _ => Bound::Unbounded,
+        };
+        let high = match rng.gen_range(0, 3) {
+            0 => Bound::Included(high),
+            1 => Bound::Excluded(high),
+            _ => Bound::Unbounded,
+        };
+        let explain = llrb.explain_range((low, high));
+        let keys: Vec<i64> = llrb.range((low, high)).map(|(k, _)| k).collect();
+        assert_eq!(explain.entries(), keys.len());
+        assert_eq!(explain.fragments(), explain.visits().len());
+        assert!(explain.depth().unwrap() <= max);
+
+        // every entry in range is visited, and only once.
+        let iter = explain.visits().iter().map(|v| *v.key());
+        let mut visits: Vec<i64> = iter.filter(|k| (low, high).contains(k)).collect();
+        visits.sort();
+        assert_eq!(visits, keys);
+
+        let mut n = explain.entries();
+        n += if let Bound::Unbounded = low { 0 } else { 1 };
+        n += if let Bound::Unbounded = high { 0 } else { 1 };
+        assert!(explain.comparisons() <= n + max);
+    }
+}
@@ -0,0 +1,225 @@
+use std::cmp;
+
+use crate::aggregate::Aggregate;
+use crate::error::Error;
+use crate::llrb::{Llrb, Node, Stats};
+
+// Maximum end among the intervals in a sub-tree.
+#[derive(Clone, Debug, PartialEq)]
+struct MaxEnd<T>(Option<T>);
+
+impl<T, V> Aggregate<(T, T), V> for MaxEnd<T>
+where
+    T: Clone + Ord,
+{
+    fn identity() -> Self {
+        MaxEnd(None)
+    }
+
+    fn lift(key: &(T, T), _value: &V) -> Self {
+        MaxEnd(Some(key.1.clone()))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        MaxEnd(cmp::max(&self.0, &other.0).clone())
+    }
+}
+
+/// IntervalLlrb index half-open intervals, `[start, end)`, along with
+/// a value for each interval, built on [`Llrb`] tree.
+///
+/// Intervals are sorted by their start, and then by their end. Each
+/// node maintains the maximum end among the intervals in its sub-tree,
+/// so that queries can skip sub-trees that cannot hold a match.
+#[derive(Clone)]
+pub struct IntervalLlrb<T, V>
+where
+    T: Clone + Ord,
+    V: Clone,
+{
+    llrb: Llrb<(T, T), V, MaxEnd<T>>,
+}
+
+impl<T, V> IntervalLlrb<T, V>
+where
+    T: Clone + Ord,
+    V: Clone,
+{
+    /// Create an empty interval index, identified by `name`.
+    pub fn new<S>(name: S) -> IntervalLlrb<T, V>
+    where
+        S: AsRef<str>,
+    {
+        IntervalLlrb {
+            llrb: Llrb::new_aggregate(name),
+        }
+    }
+
+    /// Identify this instance.
+    pub fn id(&self) -> String {
+        self.llrb.id()
+    }
+
+    /// Return number of intervals in this instance.
+    pub fn len(&self) -> usize {
+        self.llrb.len()
+    }
+
+    /// Check whether this instance is empty.
+    pub fn is_empty(&self) -> bool {
+        self.llrb.is_empty()
+    }
+
+    /// Insert interval `[start, end)` with `value`. If the same interval
+    /// is already present, overwrite its value and return the old value.
+    pub fn insert(&mut self, start: T, end: T, value: V) -> Option<V> {
+        self.llrb.set((start, end), value)
+    }
+
+    /// Get the value for interval `[start, end)`.
+    pub fn get(&self, start: &T, end: &T) -> Option<V> {
+        self.llrb.get(&(start.clone(), end.clone()))
+    }
+
+    /// Delete interval `[start, end)` and return its value.
+    pub fn delete(&mut self, start: &T, end: &T) -> Option<V> {
+        self.llrb.delete(&(start.clone(), end.clone()))
+    }
+
+    /// Return an iterator over all intervals, in sort order.
+    pub fn iter(&self) -> Intervals<'_, T, V> {
+        Intervals::new(self, Query::All)
+    }
+
+    /// Return an iterator over intervals that contain `point`, in sort
+    /// order.
+    pub fn stabbing(&self, point: T) -> Intervals<'_, T, V> {
+        Intervals::new(self, Query::Stab(point))
+    }
+
+    /// Return an iterator over intervals that overlap `[start, end)`, in
+    /// sort order.
+    pub fn overlap(&self, start: T, end: T) -> Intervals<'_, T, V> {
+        Intervals::new(self, Query::Overlap(start, end))
+    }
+
+    /// Return an iterator over intervals that enclose `[start, end)`,
+    /// in sort order.
+    pub fn enclosing(&self, start: T, end: T) -> Intervals<'_, T, V> {
+        Intervals::new(self, Query::Enclose(start, end))
+    }
+
+    /// Validate the underlying tree, refer to [`Llrb::validate`].
+    pub fn validate(&self) -> Result<Stats, Error<(T, T)>> {
+        self.llrb.validate()
+    }
+}
+
+// Query on interval index.
+enum Query<T> {
+    All,
+    Stab(T),
+    Overlap(T, T),
+    Enclose(T, T),
+}
+
+impl<T> Query<T>
+where
+    T: Clone + Ord,
+{
+    // Whether a sub-tree, with its maximum end, can be skipped.
+    fn skip(&self, max_end: &MaxEnd<T>) -> bool {
+        let max_end = match &max_end.0 {
+            Some(max_end) => max_end,
+            None => return true,
+        };
+        match self {
+            Query::All => false,
+            Query::Stab(point) => max_end <= point,
+            Query::Overlap(start, _) => max_end <= start,
+            Query::Enclose(_, end) => max_end < end,
+        }
+    }
+
+    // Whether intervals starting at `start`, or later, can match.
+    fn is_within(&self, start: &T) -> bool {
+        match self {
+            Query::All => true,
+            Query::Stab(point) => start <= point,
+            Query::Overlap(_, end) => start < end,
+            Query::Enclose(qstart, _) => start <= qstart,
+        }
+    }
+
+    fn is_match(&self, (start, end): &(T, T)) -> bool {
+        match self {
+            Query::All => true,
+            Query::Stab(point) => start <= point && point < end,
+            Query::Overlap(qstart, qend) => start < qend && end > qstart,
+            Query::Enclose(qstart, qend) => start <= qstart && end >= qend,
+        }
+    }
+}
+
+/// Intervals iterate over the result of a query on [`IntervalLlrb`].
+pub struct Intervals<'a, T, V>
+where
+    T: Clone + Ord,
+    V: Clone,
+{
+    query: Query<T>,
+    paths: Vec<&'a Node<(T, T), V, MaxEnd<T>>>,
+}
+
+impl<'a, T, V> Intervals<'a, T, V>
+where
+    T: Clone + Ord,
+    V: Clone,
+{
+    fn new(index: &'a IntervalLlrb<T, V>, query: Query<T>) -> Intervals<'a, T, V> {
+        let mut iter = Intervals {
+            query,
+            paths: vec![],
+        };
+        iter.walk_left(index.llrb.root.as_deref());
+        iter
+    }
+
+    // push the left spine of sub-tree, until a sub-tree can be skipped.
+    fn walk_left(&mut self, mut node: Option<&'a Node<(T, T), V, MaxEnd<T>>>) {
+        while let Some(nref) = node {
+            if self.query.skip(&nref.agg) {
+                break;
+            }
+            self.paths.push(nref);
+            node = nref.left_deref();
+        }
+    }
+}
+
+impl<'a, T, V> Iterator for Intervals<'a, T, V>
+where
+    T: Clone + Ord,
+    V: Clone,
+{
+    type Item = ((T, T), V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let nref = self.paths.pop()?;
+            if !self.query.is_within(&nref.key.0) {
+                // rest of the intervals start at or after this one.
+                self.paths.clear();
+                break None;
+            }
+            self.walk_left(nref.right_deref());
+            if self.query.is_match(&nref.key) {
+                break Some((nref.key.clone(), nref.value.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "interval_test.rs"]
+mod interval_test;
@@ -0,0 +1,115 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_interval() {
+    let mut index: IntervalLlrb<u64, &str> = IntervalLlrb::new("test-interval");
+    assert_eq!(index.id(), "test-interval".to_string());
+    assert!(index.is_empty());
+
+    assert!(index.insert(10, 20, "a").is_none());
+    assert!(index.insert(15, 25, "b").is_none());
+    assert!(index.insert(30, 40, "c").is_none());
+    assert!(index.insert(0, 100, "d").is_none());
+    assert_eq!(index.insert(15, 25, "e"), Some("b"));
+    assert_eq!(index.len(), 4);
+    assert_eq!(index.get(&15, &25), Some("e"));
+
+    let items: Vec<&str> = index.stabbing(20).map(|(_, v)| v).collect();
+    assert_eq!(items, vec!["d", "e"]);
+    let items: Vec<&str> = index.stabbing(10).map(|(_, v)| v).collect();
+    assert_eq!(items, vec!["d", "a"]);
+    let items: Vec<&str> = index.overlap(18, 31).map(|(_, v)| v).collect();
+    assert_eq!(items, vec!["d", "a", "e", "c"]);
+    let items: Vec<&str> = index.overlap(40, 50).map(|(_, v)| v).collect();
+    assert_eq!(items, vec!["d"]);
+    let items: Vec<&str> = index.enclosing(16, 20).map(|(_, v)| v).collect();
+    assert_eq!(items, vec!["d", "a", "e"]);
+
+    assert_eq!(index.delete(&0, &100), Some("d"));
+    assert_eq!(index.delete(&0, &100), None);
+    assert_eq!(
+        index.stabbing(35).collect::<Vec<((u64, u64), &str)>>(),
+        vec![((30, 40), "c")]
+    );
+    assert_eq!(index.iter().count(), 3);
+    assert!(index.validate().is_ok());
+}
+
+#[test]
+fn test_interval_random() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut index: IntervalLlrb<u64, u64> = IntervalLlrb::new("test-interval");
+    let mut refs: Vec<((u64, u64), u64)> = vec![];
+    for i in 0..5_000 {
+        let start = rng.gen_range(0, 10_000);
+        let end = start + rng.gen_range(1, 500);
+        match rng.gen_range(0, 3) {
+            0 if !refs.is_empty() => {
+                let ((start, end), value) = refs.remove(rng.gen_range(0, refs.len()));
+                assert_eq!(index.delete(&start, &end), Some(value));
+            }
+            _ => {
+                let old = index.insert(start, end, i);
+                match refs.iter().position(|(k, _)| *k == (start, end)) {
+                    Some(off) => assert_eq!(Some(std::mem::replace(&mut refs[off].1, i)), old),
+                    None => {
+                        assert!(old.is_none());
+                        refs.push(((start, end), i));
+                    }
+                }
+            }
+        }
+    }
+    refs.sort();
+    assert_eq!(index.len(), refs.len());
+    assert!(index.validate().is_ok());
+    assert!(index.iter().eq(refs.iter().cloned()));
+    validate_max_end(index.llrb.root.as_deref());
+
+    for _ in 0..1_000 {
+        let (qs, qe) = {
+            let qs = rng.gen_range(0, 11_000);
+            (qs, qs + rng.gen_range(0, 100))
+        };
+
+        let items: Vec<((u64, u64), u64)> = index.stabbing(qs).collect();
+        let expected: Vec<((u64, u64), u64)> = refs
+            .iter()
+            .filter(|((s, e), _)| *s <= qs && qs < *e)
+            .cloned()
+            .collect();
+        assert_eq!(items, expected);
+
+        let items: Vec<((u64, u64), u64)> = index.overlap(qs, qe).collect();
+        let expected: Vec<((u64, u64), u64)> = refs
+            .iter()
+            .filter(|((s, e), _)| *s < qe && *e > qs)
+            .cloned()
+            .collect();
+        assert_eq!(items, expected);
+
+        let items: Vec<((u64, u64), u64)> = index.enclosing(qs, qe).collect();
+        let expected: Vec<((u64, u64), u64)> = refs
+            .iter()
+            .filter(|((s, e), _)| *s <= qs && *e >= qe)
+            .cloned()
+            .collect();
+        assert_eq!(items, expected);
+    }
+}
+
+// check that every node holds the maximum end within its sub-tree.
+fn validate_max_end(node: Option<&Node<(u64, u64), u64, MaxEnd<u64>>>) -> Option<u64> {
+    let node = node?;
+    let left = validate_max_end(node.left_deref());
+    let right = validate_max_end(node.right_deref());
+    let max_end = cmp::max(cmp::max(left, right), Some(node.key.1));
+    assert_eq!(node.agg, MaxEnd(max_end));
+    max_end
+}
@@ -107,13 +107,51 @@
 //! ```
 //!
 //! [wiki-llrb]: https://en.wikipedia.org/wiki/Left-leaning_red-black_tree
+mod aggregate;
+mod batch;
+mod build;
 mod depth;
+mod diff;
 mod empty;
 mod error;
+mod evict;
+mod explain;
+mod interval;
 mod llrb;
+mod metrics;
+mod multi;
+mod observe;
+mod par;
+mod prefix;
+mod render;
+mod sample;
+mod set;
+mod shape;
+mod ttl;
+mod txn;
+mod watch;
 
+#[cfg(test)]
+mod test_util;
+
+pub use crate::aggregate::{Aggregate, Count};
+pub use crate::batch::WriteBatch;
+pub use crate::build::Duplicates;
 pub use crate::depth::Depth;
+pub use crate::diff::Change;
 pub use crate::empty::Empty;
 pub use crate::error::Error;
+pub use crate::evict::{Capacity, Eviction};
+pub use crate::explain::{Explain, Visit};
+pub use crate::interval::IntervalLlrb;
 pub use crate::llrb::Llrb;
 pub use crate::llrb::Stats;
+pub use crate::llrb::{ValidationReport, Violation};
+pub use crate::metrics::Counters;
+pub use crate::multi::LlrbMulti;
+pub use crate::observe::Event;
+pub use crate::par::ParIter;
+pub use crate::set::LlrbSet;
+pub use crate::shape::{Shape, ShapeNode};
+pub use crate::txn::{Savepoint, Txn};
+pub use crate::watch::WatchId;
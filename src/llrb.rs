@@ -2,35 +2,56 @@ use std::{
     borrow::Borrow,
     cmp::{Ord, Ordering},
     marker, mem,
-    ops::{Bound, Deref, DerefMut, RangeBounds},
+    ops::{Bound, DerefMut, RangeBounds},
+    time::{Duration, Instant},
 };
 
 use rand::Rng;
 
+use crate::aggregate::Aggregate;
+use crate::build::rebuild_tree;
 use crate::depth::Depth;
 use crate::error::Error;
+use crate::evict::{Capacity, Eviction, Evictor, Position, NO_SLOT};
+use crate::metrics::{Counter, Counters, Metrics};
+use crate::observe::{Event, Observer};
+use crate::ttl::{Expiry, Live};
 
 // TODO: replace id() with to_name().
 
 /// Llrb manage a single instance of in-memory index using
 /// [left-leaning-red-black][llrb] tree.
 ///
+/// Write operations are panic safe. If `Ord` or `Clone` on keys panics
+/// midway through a write, and the panic is caught, the index remains
+/// valid and consistent with its entries. The failed write itself might,
+/// or might not, have taken effect.
+///
 /// [llrb]: https://en.wikipedia.org/wiki/Left-leaning_red-black_tree
 #[derive(Clone)]
-pub struct Llrb<K, V>
+pub struct Llrb<K, V, A = ()>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     name: String,
-    root: Option<Box<Node<K, V>>>,
-    n_count: usize, // number of entries in the tree.
+    pub(crate) root: Option<Box<Node<K, V, A>>>,
+    pub(crate) n_count: usize, // number of entries in the tree.
+    evictor: Option<Evictor<K, V>>,
+    evicted: Vec<(K, V)>,
+    pub(crate) expiry: Expiry<K>,
+    pub(crate) n_expired: usize, // number of entries removed after their deadline.
+    paranoid: bool,              // check invariants after every mutation.
+    pub(crate) metrics: Metrics,
+    pub(crate) observer: Observer<K, V>,
 }
 
-impl<K, V> Extend<(K, V)> for Llrb<K, V>
+impl<K, V, A> Extend<(K, V)> for Llrb<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -51,6 +72,37 @@ where
     /// Create an empty instance of Llrb, identified by `name`.
     /// Applications can choose unique names.
     pub fn new<S>(name: S) -> Llrb<K, V>
+    where
+        S: AsRef<str>,
+    {
+        Llrb::new_aggregate(name)
+    }
+
+    /// Create an empty instance of Llrb, identified by `name`, that
+    /// shall not grow beyond `capacity`. When a write operation exceeds
+    /// the capacity, entries are evicted as per `policy` and can be
+    /// collected via [`Llrb::drain_evicted`].
+    pub fn new_bounded<S>(name: S, capacity: Capacity, policy: Eviction) -> Llrb<K, V>
+    where
+        S: AsRef<str>,
+    {
+        let mut llrb = Llrb::new(name);
+        let node_size = mem::size_of::<Node<K, V>>();
+        llrb.evictor = Some(Evictor::new(capacity, policy, node_size));
+        llrb
+    }
+}
+
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Create an empty instance of Llrb, identified by `name`, that
+    /// maintains the aggregate `A` for each of its sub-trees. Refer to
+    /// [`Aggregate`] and [`Llrb::fold_range`].
+    pub fn new_aggregate<S>(name: S) -> Llrb<K, V, A>
     where
         S: AsRef<str>,
     {
@@ -58,15 +110,23 @@ where
             name: name.as_ref().to_string(),
             root: Default::default(),
             n_count: Default::default(),
+            evictor: Default::default(),
+            evicted: Default::default(),
+            expiry: Default::default(),
+            n_expired: Default::default(),
+            paranoid: cfg!(feature = "paranoid"),
+            metrics: Default::default(),
+            observer: Default::default(),
         }
     }
 }
 
 /// Maintenance API.
-impl<K, V> Llrb<K, V>
+impl<K, V, A> Llrb<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     /// Identify this instance. Applications can choose unique names while
     /// creating Llrb instances.
@@ -90,51 +150,314 @@ where
     /// Return quickly with basic statisics, only entries() method is valid
     /// with this statisics.
     pub fn stats(&self) -> Stats {
-        Stats::new(self.n_count, mem::size_of::<Node<K, V>>())
+        let mut stats = Stats::new(self.n_count, mem::size_of::<Node<K, V, A>>());
+        stats.set_expired(self.n_expired);
+        stats.set_counters(self.metrics.to_counters());
+        stats
+    }
+
+    /// Reset operation counters, refer to [`Stats::counters`].
+    pub fn reset_counters(&mut self) {
+        self.metrics.reset()
+    }
+
+    /// Return the capacity and eviction policy, if this instance was
+    /// created using [`Llrb::new_bounded`].
+    pub fn capacity(&self) -> Option<(Capacity, Eviction)> {
+        let evictor = self.evictor.as_ref()?;
+        Some((evictor.capacity(), evictor.policy()))
+    }
+
+    /// Return the memory footprint, in bytes, accounted for the entries
+    /// in a bounded instance.
+    pub fn footprint(&self) -> Option<usize> {
+        self.evictor.as_ref().map(Evictor::bytes)
+    }
+
+    /// Account heap memory owned by keys and values, in addition to the
+    /// node-size, while enforcing [`Capacity::Bytes`]. Entries already
+    /// in the index are re-accounted, which might evict entries.
+    pub fn set_footprint(&mut self, footprint: fn(&K, &V) -> usize) {
+        if self.evictor.is_none() {
+            return;
+        }
+        let node_size = mem::size_of::<Node<K, V, A>>();
+        let bytes = self
+            .iter()
+            .map(|(k, v)| node_size + footprint(&k, &v))
+            .sum();
+        let evictor = self.evictor.as_mut().unwrap();
+        evictor.set_footprint(footprint);
+        evictor.set_bytes(bytes);
+        self.evict();
+    }
+
+    /// Return entries evicted so far, and clear them from this instance.
+    pub fn drain_evicted(&mut self) -> Vec<(K, V)> {
+        mem::take(&mut self.evicted)
+    }
+
+    /// Check LLRB invariants after every mutation, and panic naming the
+    /// operation that broke the tree. Useful while debugging a custom
+    /// `Ord` implementation for keys. Checks are limited to the path
+    /// walked by the mutation, costing O(log^2 n) per operation.
+    ///
+    /// Enabled by default when compiled with the `paranoid` feature.
+    pub fn set_paranoid(&mut self, paranoid: bool) {
+        self.paranoid = paranoid;
+    }
+
+    /// Return whether invariants are checked after every mutation, refer
+    /// to [`Llrb::set_paranoid`].
+    pub fn is_paranoid(&self) -> bool {
+        self.paranoid
     }
 }
 
-type Insert<K, V> = (Box<Node<K, V>>, Option<Error<K>>);
+type Delmin<K, V, A> = (Option<Box<Node<K, V, A>>>, Node<K, V, A>);
 
-type Upsert<K, V> = (Box<Node<K, V>>, Option<V>);
+// Nodes detached from the tree while walking down, along with the side
+// taken from each of them.
+type Path<K, V, A> = Vec<(Box<Node<K, V, A>>, Side)>;
 
-type Delete<K, V> = (Option<Box<Node<K, V>>>, Option<V>);
+// Walk holds the nodes detached from the tree while walking down, until
+// they are re-attached on the way up. If `Ord` panics midway, detached
+// nodes are re-attached while unwinding, and the tree is rebuilt if it
+// was re-balanced on the way down, so that the tree remains valid
+// without losing entries.
+struct Walk<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    root: &'a mut Option<Box<Node<K, V, A>>>,
+    node: Option<Box<Node<K, V, A>>>,
+    path: Path<K, V, A>,
+    rebuild: bool, // nodes were re-balanced on the way down.
+    metrics: &'a Metrics,
+}
 
-type Delmin<K, V> = (Option<Box<Node<K, V>>>, Option<Node<K, V>>);
+impl<'a, K, V, A> Walk<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn new(
+        root: &'a mut Option<Box<Node<K, V, A>>>,
+        height: usize,
+        metrics: &'a Metrics,
+    ) -> Walk<'a, K, V, A> {
+        Walk {
+            node: root.take(),
+            root,
+            path: Vec::with_capacity(height),
+            rebuild: false,
+            metrics,
+        }
+    }
+
+    // detach the child on `side` and walk down to it.
+    fn push(&mut self, side: Side) {
+        let mut nref = self.node.take().unwrap();
+        self.node = match side {
+            Side::Left => nref.left.take(),
+            Side::Right => nref.right.take(),
+        };
+        self.path.push((nref, side));
+    }
+
+    // re-balance the current node on the way down.
+    fn rebalance<F>(&mut self, f: F)
+    where
+        F: FnOnce(Box<Node<K, V, A>>, &Metrics) -> Box<Node<K, V, A>>,
+    {
+        self.rebuild = true;
+        self.node = self.node.take().map(|node| f(node, self.metrics));
+    }
+
+    // re-attach the detached nodes after an insert, rotating them on
+    // the way up.
+    fn walkup(&mut self) {
+        let node = self.node.take().unwrap();
+        let path = mem::take(&mut self.path);
+        let mut root = Llrb::walkup(node, path, self.metrics);
+        root.set_black();
+        *self.root = Some(root);
+    }
+
+    // re-attach the detached nodes after a delete, fixing them up on
+    // the way up.
+    fn fixup(&mut self) {
+        let mut node = self.node.take();
+        while let Some((mut parent, side)) = self.path.pop() {
+            parent.set_child(side, node);
+            node = Some(Llrb::fixup(parent, self.metrics));
+        }
+        if let Some(root) = node.as_mut() {
+            root.set_black();
+        }
+        *self.root = node;
+    }
+}
+
+impl<'a, K, V, A> Drop for Walk<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn drop(&mut self) {
+        if self.node.is_none() && self.path.is_empty() {
+            return;
+        }
+        // unwinding from a panic, re-attach the nodes as they are.
+        let mut node = self.node.take();
+        while let Some((mut parent, side)) = self.path.pop() {
+            match side {
+                Side::Left => parent.left = node,
+                Side::Right => parent.right = node,
+            }
+            node = Some(parent);
+        }
+        if self.rebuild {
+            node = rebuild_tree(node);
+        }
+        *self.root = node;
+    }
+}
+
+// Frame, while validating the tree, holds a node, whether it is red,
+// number of blacks from root including the node, its depth and, once
+// validated, number of blacks under its left child.
+type Frame<'a, K, V, A> = (&'a Node<K, V, A>, bool, usize, usize, Option<usize>);
+
+// Return the height of the tallest path possible in a LLRB tree holding
+// `n` entries, which is twice the height of its 2-3 tree.
+pub(crate) fn max_height(n: usize) -> usize {
+    2 * ((usize::BITS - n.leading_zeros()) as usize) + 2
+}
 
 /// Write operations on Llrb instance.
-impl<K, V> Llrb<K, V>
+impl<K, V, A> Llrb<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     /// Create a new {key, value} entry in the index. If key is already
     /// present return error.
     pub fn create(&mut self, key: K, value: V) -> Result<(), Error<K>> {
-        let (mut root, error) = Llrb::insert(self.root.take(), key, value);
-        root.set_black();
-        self.root = Some(root);
-        match error {
-            Some(err) => Err(err),
-            None => {
-                self.n_count += 1;
-                Ok(())
-            }
-        }
+        self.do_create(key, value)?;
+        self.evict();
+        Ok(())
     }
 
     /// Set value for key. If there is an existing entry for key,
     /// overwrite the old value with new value and return the old value.
     pub fn set(&mut self, key: K, value: V) -> Option<V> {
-        let (mut root, old_value) = Llrb::upsert(self.root.take(), key, value);
-        root.set_black();
-        self.root = Some(root);
+        let old_value = self.do_set(key, value, None);
+        self.evict();
+        old_value
+    }
+
+    /// Set value for key, that shall expire after `ttl`. Once expired, the
+    /// entry is hidden from read operations, and is removed from the
+    /// index by [`Llrb::expire`]. Return the old value, if any.
+    pub fn set_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let old_value = self.do_set(key, value, Some(Instant::now() + ttl));
+        self.evict();
+        old_value
+    }
+
+    // Same as create(), without evicting entries.
+    pub(crate) fn do_create(&mut self, key: K, value: V) -> Result<(), Error<K>> {
+        if self.is_expired(&key) {
+            self.delete(&key);
+        }
+        let entry = self.to_account(&key, &value);
+        let probe = if self.paranoid {
+            Some(key.clone())
+        } else {
+            None
+        };
+        let change = self.to_change(&key, &value);
+        let height = max_height(self.n_count);
+        let slot = entry.as_ref().map_or(NO_SLOT, |(_, _, slot)| *slot);
+        let error = Llrb::insert(&mut self.root, key, value, slot, height, &self.metrics);
+        match error {
+            Some(_) => self.metrics.incr(Counter::FailedCreates),
+            None => self.metrics.incr(Counter::Creates),
+        }
+        if error.is_none() {
+            self.n_count += 1;
+            if let Some((key, cost, slot)) = entry {
+                self.evictor.as_mut().unwrap().inserted(slot, key, cost);
+            }
+        }
+        if let Some(key) = probe {
+            self.check_path(&key, "create");
+        }
+        if error.is_none() {
+            let event = change.map(|(key, value)| Event::Inserted(key, value));
+            self.observer.record(event);
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    // Same as set(), without evicting entries. Entry shall expire at
+    // `deadline`, if supplied.
+    pub(crate) fn do_set(&mut self, key: K, value: V, deadline: Option<Instant>) -> Option<V> {
+        let expired = self.is_expired(&key);
+        let entry = self.to_account(&key, &value);
+        // a deadline is dropped before the tree is touched, and added
+        // once the tree is set, so that a panic midway shall not leave
+        // behind a deadline without its entry.
+        if deadline.is_none() && !self.expiry.is_empty() {
+            self.expiry.remove(&key);
+        }
+        let probe = match deadline {
+            None if !self.paranoid => None,
+            _ => Some(key.clone()),
+        };
+        let change = self.to_change(&key, &value);
+        let height = max_height(self.n_count);
+        let mut slot = entry.as_ref().map_or(NO_SLOT, |(_, _, slot)| *slot);
+        let old_value = Llrb::upsert(&mut self.root, key, value, &mut slot, height, &self.metrics);
+        self.metrics.incr(Counter::Sets);
+        if old_value.is_none() {
+            self.n_count += 1;
+        }
+        if let Some((key, cost, _)) = entry {
+            let evictor = self.evictor.as_mut().unwrap();
+            match &old_value {
+                Some(old_value) => evictor.updated(slot, &key, old_value, cost),
+                None => evictor.inserted(slot, key, cost),
+            }
+        }
+        if let Some(key) = probe {
+            if let Some(deadline) = deadline {
+                self.expiry.set(key.clone(), deadline);
+            }
+            if self.paranoid {
+                self.check_path(&key, "set");
+            }
+        }
+        let event = change.map(|(key, value)| match &old_value {
+            Some(old_value) => Event::Updated(key, old_value.clone(), value),
+            None => Event::Inserted(key, value),
+        });
+        self.observer.record(event);
         match old_value {
-            old_value @ Some(_) => old_value,
-            None => {
-                self.n_count += 1;
+            Some(_) if expired => {
+                self.n_expired += 1;
                 None
             }
+            old_value => old_value,
         }
     }
 
@@ -145,44 +468,246 @@ where
         K: Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        let (root, old_value) = match Llrb::do_delete(self.root.take(), key) {
-            (None, old_value) => (None, old_value),
-            (Some(mut root), old_value) => {
-                root.set_black();
-                (Some(root), old_value)
+        let (_, value, deadline) = self.remove(key)?;
+        match deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                self.n_expired += 1;
+                None
             }
+            _ => Some(value),
+        }
+    }
+
+    /// Remove all entries from this instance. Deadlines of entries set
+    /// with a ttl, and the access order of entries in a bounded instance,
+    /// are forgotten as well.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.n_count = 0;
+        if let Some(evictor) = self.evictor.as_mut() {
+            evictor.cleared();
+        }
+        self.expiry = Default::default();
+        self.observer.record(Some(Event::Cleared));
+    }
+
+    /// Remove entries whose deadline has lapsed as of `now`, visiting no
+    /// more than `budget` entries. Return the number of entries removed.
+    /// Applications can call this periodically, to reclaim memory held
+    /// by expired entries, without stalling on a large index.
+    pub fn expire(&mut self, now: Instant, budget: usize) -> usize {
+        let mut n = 0;
+        for _ in 0..budget {
+            let key = match self.expiry.pop_expired(now) {
+                Some(key) => key,
+                None => break,
+            };
+            // deadline might have outlived its entry.
+            if self.remove(&key).is_some() {
+                self.n_expired += 1;
+                n += 1;
+            }
+        }
+        n
+    }
+
+    // Remove entry from the tree, and its book-keeping, and return the
+    // entry along with its deadline. Deadline is dropped before the tree
+    // is touched, so that a panic midway shall not leave behind a
+    // deadline without its entry.
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<(K, V, Option<Instant>)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let deadline = if self.expiry.is_empty() {
+            None
+        } else {
+            self.expiry.remove(key)
+        };
+        let height = max_height(self.n_count);
+        let old_entry = Llrb::do_delete(&mut self.root, key, height, &self.metrics);
+        self.metrics.incr(Counter::Deletes);
+        let old_entry = match old_entry {
+            Some((old_key, old_value, slot)) => {
+                self.n_count -= 1;
+                if let Some(evictor) = self.evictor.as_mut() {
+                    evictor.removed(slot, &old_key, &old_value);
+                }
+                Some((old_key, old_value, deadline))
+            }
+            None => None,
         };
-        self.root = root;
-        if old_value.is_some() {
-            self.n_count -= 1;
+        if self.paranoid {
+            self.check_path(key, "delete");
         }
-        old_value
+        if let Some((key, value, _)) = old_entry.as_ref() {
+            let change = self.to_change(key, value);
+            let event = change.map(|(key, value)| Event::Deleted(key, value));
+            self.observer.record(event);
+        }
+        old_entry
     }
 
     /// Validate LLRB tree with following rules:
     ///
+    /// * Root is black.
     /// * From root to any leaf, no consecutive reds allowed in its path.
+    /// * Red links lean left, that is, no red right child.
     /// * Number of blacks should be same under left child and right child.
-    /// * Make sure keys are in sorted order.
+    /// * Make sure keys are in sorted order, across sub-trees.
+    /// * Number of nodes in the tree matches the entry count.
     ///
     /// Additionally return full statistics on the tree. Refer to [`Stats`]
-    /// for more information.
+    /// for more information. On failure, return the first violation,
+    /// refer to [`Llrb::validate_report`] for all violations.
     pub fn validate(&self) -> Result<Stats, Error<K>> {
-        let root = self.root.as_ref().map(Deref::deref);
-        let (red, nb, d) = (is_red(root), 0, 0);
-        let mut stats = Stats::new(self.n_count, mem::size_of::<Node<K, V>>());
+        let report = self.validate_report();
+        match report.violations.into_iter().next() {
+            Some(violation) => Err(violation.error),
+            None => Ok(report.stats),
+        }
+    }
+
+    /// Same as [`Llrb::validate`], except that the tree is walked in
+    /// full and every violation is reported, along with the path to the
+    /// offending node.
+    pub fn validate_report(&self) -> ValidationReport<K> {
+        let root = self.root.as_deref();
+        let mut stats = Stats::new(self.n_count, mem::size_of::<Node<K, V, A>>());
         stats.set_depths(Depth::new());
-        let blacks = Llrb::validate_tree(root, red, nb, d, &mut stats)?;
+        let mut violations = vec![];
+        if let Some(root) = root.filter(|root| !root.is_black()) {
+            let path = vec![root.key.clone()];
+            violations.push(Violation::new(path, Error::RedRoot));
+        }
+        let height = max_height(self.n_count);
+        let (blacks, n_nodes) = Llrb::validate_tree(root, height, &mut stats, &mut violations);
+        if n_nodes != self.n_count {
+            let (count, nodes) = (self.n_count, n_nodes);
+            violations.push(Violation::new(
+                vec![],
+                Error::CountMismatch { count, nodes },
+            ));
+        }
         stats.set_blacks(blacks);
-        Ok(stats)
+        stats.set_expired(self.n_expired);
+        stats.set_counters(self.metrics.to_counters());
+        ValidationReport { stats, violations }
+    }
+
+    // Check invariants on nodes along the path to key, and on the left
+    // spine of their sub-trees, which covers the nodes re-balanced by
+    // a mutation on key. Panic naming the operation `op` on violation.
+    fn check_path<Q>(&self, key: &Q, op: &str)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Err((depth, what)) = self.do_check_path(key) {
+            panic!(
+                "{}: {}() broke the tree, {} at depth {}",
+                self.name, op, what, depth
+            );
+        }
+    }
+
+    fn do_check_path<Q>(&self, key: &Q) -> Result<(), (usize, &'static str)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        if is_red(node) {
+            return Err((0, "red root"));
+        }
+        let (mut low, mut high): (Option<&K>, Option<&K>) = (None, None);
+        let (mut fromred, mut depth) = (false, 0);
+        while let Some(nref) = node {
+            check_node(nref, low, high, fromred, depth)?;
+            let (red, key_n) = (is_red(node), Some(&nref.key));
+            let lblacks = check_spine(nref.left_deref(), low, key_n, red, depth + 1)?;
+            let rblacks = check_spine(nref.right_deref(), key_n, high, red, depth + 1)?;
+            if lblacks != rblacks {
+                return Err((depth, "unbalanced blacks"));
+            }
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => {
+                    low = key_n;
+                    nref.right_deref()
+                }
+                Ordering::Greater => {
+                    high = key_n;
+                    nref.left_deref()
+                }
+                Ordering::Equal => None,
+            };
+            fromred = red;
+            depth += 1;
+        }
+        Ok(())
+    }
+
+    // Check invariants on the whole tree, after a bulk operation `op`.
+    pub(crate) fn check_tree(&self, op: &str) {
+        let report = self.validate_report();
+        if let Some(violation) = report.violations().first() {
+            let depth = violation.path().len();
+            let what = violation.error().to_invariant();
+            panic!(
+                "{}: {}() broke the tree, {} at depth {}",
+                self.name, op, what, depth
+            );
+        }
+    }
+
+    // Return the key, its cost and the slot it shall take in access
+    // order, for book-keeping, if this instance is bounded. Key is cloned
+    // before the tree is touched, so that a panicking clone leaves the
+    // book-keeping consistent with the tree.
+    fn to_account(&self, key: &K, value: &V) -> Option<(K, usize, u32)> {
+        let evictor = self.evictor.as_ref()?;
+        Some((key.clone(), evictor.cost(key, value), evictor.vacant()))
+    }
+
+    // Return a copy of the entry, for change-data-capture, if this
+    // instance is observed.
+    fn to_change(&self, key: &K, value: &V) -> Option<(K, V)> {
+        match self.observer.is_active() {
+            true => Some((key.clone(), value.clone())),
+            false => None,
+        }
+    }
+
+    // Evict entries, as per eviction policy, until this instance fits
+    // within its capacity.
+    pub(crate) fn evict(&mut self) {
+        loop {
+            let key = match self.evictor.as_ref() {
+                Some(evictor) if evictor.is_over(self.n_count) => match evictor.policy() {
+                    Eviction::Lru | Eviction::Oldest => evictor.victim(),
+                    Eviction::Smallest => self.min_key(),
+                    Eviction::Largest => self.max_key(),
+                },
+                _ => None,
+            };
+            match key.and_then(|key| self.remove(&key)) {
+                Some((_, _, Some(deadline))) if deadline <= Instant::now() => {
+                    self.n_expired += 1;
+                }
+                Some((key, value, _)) => self.evicted.push((key, value)),
+                None => break,
+            }
+        }
     }
 }
 
 /// Read operations on Llrb instance.
-impl<K, V> Llrb<K, V>
+impl<K, V, A> Llrb<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     /// Get the value for key.
     pub fn get<Q>(&self, key: &Q) -> Option<V>
@@ -190,20 +715,137 @@ where
         K: Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        let mut node = self.root.as_ref().map(Deref::deref);
+        self.metrics.incr(Counter::Gets);
+        let mut node = self.root.as_deref();
+        while let Some(nref) = node {
+            self.metrics.incr(Counter::Comparisons);
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => nref.right_deref(),
+                Ordering::Greater => nref.left_deref(),
+                Ordering::Equal if self.is_expired(key) => return None,
+                Ordering::Equal => {
+                    if let Some(evictor) = self.evictor.as_ref() {
+                        evictor.accessed(nref.slot);
+                    }
+                    return Some(nref.value.clone());
+                }
+            };
+        }
+        None
+    }
+
+    // Return the entry for key, along with its deadline, whether it is
+    // expired or not.
+    pub(crate) fn entry<Q>(&self, key: &Q) -> Option<(K, V, Option<Instant>)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        while let Some(nref) = node {
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => nref.right_deref(),
+                Ordering::Greater => nref.left_deref(),
+                Ordering::Equal => {
+                    let deadline = self.expiry.deadline(key);
+                    return Some((nref.key.clone(), nref.value.clone(), deadline));
+                }
+            };
+        }
+        None
+    }
+
+    // Return the position of key in access order, if this instance is
+    // bounded by Lru or Oldest policy.
+    pub(crate) fn access_position<Q>(&self, key: &Q) -> Option<Position>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let slot = self.find(key)?.slot;
+        self.evictor.as_ref()?.position(slot)
+    }
+
+    // Move key back to a prior position in access order, refer to
+    // Llrb::access_position().
+    pub(crate) fn reposition<Q>(&mut self, key: &Q, position: Position)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(slot) = self.find(key).map(|nref| nref.slot) {
+            if let Some(evictor) = self.evictor.as_mut() {
+                evictor.reposition(slot, position)
+            }
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<&Node<K, V, A>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
         while let Some(nref) = node {
             node = match nref.key.borrow().cmp(key) {
                 Ordering::Less => nref.right_deref(),
                 Ordering::Greater => nref.left_deref(),
-                Ordering::Equal => return Some(nref.value.clone()),
+                Ordering::Equal => return Some(nref),
             };
         }
         None
     }
 
-    /// Return a random entry from this index.
+    // Check whether key is present, without counting it as an access.
+    pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        while let Some(nref) = node {
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => nref.right_deref(),
+                Ordering::Greater => nref.left_deref(),
+                Ordering::Equal => return !self.is_expired(key),
+            };
+        }
+        false
+    }
+
+    pub(crate) fn is_expired<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        !self.expiry.is_empty() && self.expiry.is_expired(key, Instant::now())
+    }
+
+    pub(crate) fn live(&self) -> Option<Live<'_, K>> {
+        Live::new(&self.expiry, Instant::now())
+    }
+
+    fn min_key(&self) -> Option<K> {
+        let mut nref = self.root.as_deref()?;
+        while let Some(left) = nref.left_deref() {
+            nref = left;
+        }
+        Some(nref.key.clone())
+    }
+
+    fn max_key(&self) -> Option<K> {
+        let mut nref = self.root.as_deref()?;
+        while let Some(right) = nref.right_deref() {
+            nref = right;
+        }
+        Some(nref.key.clone())
+    }
+
+    /// Return a random entry from this index. This is cheap, but biased
+    /// towards entries near the root, refer to [`Llrb::sample_uniform`]
+    /// for statistical sampling.
     pub fn random<R: Rng>(&self, rng: &mut R) -> Option<(K, V)> {
-        let mut nref = self.root.as_ref().map(Deref::deref)?;
+        let mut nref = self.root.as_deref()?;
 
         let mut at_depth = rng.gen::<u8>() % 40;
         loop {
@@ -221,238 +863,375 @@ where
     }
 
     /// Return an iterator over all entries in this instance.
-    pub fn iter(&self) -> Iter<K, V> {
-        let node = self.root.as_ref().map(Deref::deref);
+    pub fn iter(&self) -> Iter<'_, K, V, A> {
+        let node = self.root.as_deref();
+        let mut paths = Vec::with_capacity(max_height(self.n_count));
+        build_iter(IFlag::Left, node, &mut paths);
         Iter {
-            paths: Some(build_iter(IFlag::Left, node, vec![])),
+            paths,
+            live: self.live(),
         }
     }
 
     /// Range over all entries from low to high.
-    pub fn range<Q, R>(&self, range: R) -> Range<K, V, R, Q>
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, R, Q, A>
     where
         K: Borrow<Q>,
         R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        let root = self.root.as_ref().map(Deref::deref);
-        let paths = match range.start_bound() {
-            Bound::Unbounded => Some(build_iter(IFlag::Left, root, vec![])),
-            Bound::Included(low) => Some(find_start(root, low, true, vec![])),
-            Bound::Excluded(low) => Some(find_start(root, low, false, vec![])),
+        let root = self.root.as_deref();
+        let mut paths = Vec::with_capacity(max_height(self.n_count));
+        match range.start_bound() {
+            Bound::Unbounded => build_iter(IFlag::Left, root, &mut paths),
+            Bound::Included(low) => find_start(root, low, true, &mut paths),
+            Bound::Excluded(low) => find_start(root, low, false, &mut paths),
         };
         let high = marker::PhantomData;
-        Range { range, paths, high }
+        let live = self.live();
+        Range {
+            range,
+            paths,
+            live,
+            high,
+        }
     }
 
     /// Reverse range over all entries from high to low.
-    pub fn reverse<R, Q>(&self, range: R) -> Reverse<K, V, R, Q>
+    pub fn reverse<R, Q>(&self, range: R) -> Reverse<'_, K, V, R, Q, A>
     where
         K: Borrow<Q>,
         R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        let root = self.root.as_ref().map(Deref::deref);
-        let paths = match range.end_bound() {
-            Bound::Unbounded => Some(build_iter(IFlag::Right, root, vec![])),
-            Bound::Included(high) => Some(find_end(root, high, true, vec![])),
-            Bound::Excluded(high) => Some(find_end(root, high, false, vec![])),
+        let root = self.root.as_deref();
+        let mut paths = Vec::with_capacity(max_height(self.n_count));
+        match range.end_bound() {
+            Bound::Unbounded => build_iter(IFlag::Right, root, &mut paths),
+            Bound::Included(high) => find_end(root, high, true, &mut paths),
+            Bound::Excluded(high) => find_end(root, high, false, &mut paths),
         };
         let low = marker::PhantomData;
-        Reverse { range, paths, low }
+        let live = self.live();
+        Reverse {
+            range,
+            paths,
+            live,
+            low,
+        }
+    }
+}
+
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    // Return a mutable reference to the value for key, whether it is
+    // expired or not. Value is updated in place, bypassing the
+    // book-keeping for bounded instances, hence meant for unbounded
+    // instances without aggregate.
+    pub(crate) fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref_mut();
+        while let Some(nref) = node {
+            node = match nref.key.borrow().cmp(key) {
+                Ordering::Less => nref.right.as_deref_mut(),
+                Ordering::Greater => nref.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut nref.value),
+            };
+        }
+        None
     }
 }
 
-impl<K, V> Llrb<K, V>
+impl<K, V, A> Llrb<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    fn insert(node: Option<Box<Node<K, V>>>, key: K, value: V) -> Insert<K, V> {
-        if node.is_none() {
-            return (Node::new(key, value, false /*black*/), None);
-        }
-
-        let mut node = Llrb::walkdown_rot23(node.unwrap());
-
-        match node.key.cmp(&key) {
-            Ordering::Greater => {
-                let (left, e) = Llrb::insert(node.left.take(), key, value);
-                node.left = Some(left);
-                (Llrb::walkuprot_23(node), e)
-            }
-            Ordering::Less => {
-                let (right, e) = Llrb::insert(node.right.take(), key, value);
-                node.right = Some(right);
-                (Llrb::walkuprot_23(node), e)
-            }
-            Ordering::Equal => {
-                let err = Some(Error::OverwriteKey);
-                (Llrb::walkuprot_23(node), err)
+    fn insert(
+        root: &mut Option<Box<Node<K, V, A>>>,
+        key: K,
+        value: V,
+        slot: u32,
+        height: usize,
+        metrics: &Metrics,
+    ) -> Option<Error<K>> {
+        let mut walk = Walk::new(root, height, metrics);
+        let error = loop {
+            let nref = match walk.node.take() {
+                None => {
+                    let mut node = Node::new(key, value, false /*black*/);
+                    node.slot = slot;
+                    walk.node = Some(node);
+                    break None;
+                }
+                Some(nref) => walk.node.insert(Llrb::walkdown_rot23(nref)),
+            };
+            metrics.incr(Counter::Comparisons);
+            match nref.key.cmp(&key) {
+                Ordering::Greater => walk.push(Side::Left),
+                Ordering::Less => walk.push(Side::Right),
+                Ordering::Equal => break Some(Error::OverwriteKey),
             }
-        }
+        };
+        walk.walkup();
+        error
     }
 
-    fn upsert(node: Option<Box<Node<K, V>>>, key: K, value: V) -> Upsert<K, V> {
-        if node.is_none() {
-            return (Node::new(key, value, false /*black*/), None);
-        }
-
-        let mut node = Llrb::walkdown_rot23(node.unwrap());
-
-        match node.key.cmp(&key) {
-            Ordering::Greater => {
-                let (left, o) = Llrb::upsert(node.left.take(), key, value);
-                node.left = Some(left);
-                (Llrb::walkuprot_23(node), o)
-            }
-            Ordering::Less => {
-                let (right, o) = Llrb::upsert(node.right.take(), key, value);
-                node.right = Some(right);
-                (Llrb::walkuprot_23(node), o)
-            }
-            Ordering::Equal => {
-                let old_value = node.value.clone();
-                node.set_value(value);
-                (Llrb::walkuprot_23(node), Some(old_value))
+    // new node takes `slot`, while an existing node hands back its slot.
+    fn upsert(
+        root: &mut Option<Box<Node<K, V, A>>>,
+        key: K,
+        value: V,
+        slot: &mut u32,
+        height: usize,
+        metrics: &Metrics,
+    ) -> Option<V> {
+        let mut walk = Walk::new(root, height, metrics);
+        let old_value = loop {
+            let nref = match walk.node.take() {
+                None => {
+                    let mut node = Node::new(key, value, false /*black*/);
+                    node.slot = *slot;
+                    walk.node = Some(node);
+                    break None;
+                }
+                Some(nref) => walk.node.insert(Llrb::walkdown_rot23(nref)),
+            };
+            metrics.incr(Counter::Comparisons);
+            match nref.key.cmp(&key) {
+                Ordering::Greater => walk.push(Side::Left),
+                Ordering::Less => walk.push(Side::Right),
+                Ordering::Equal => {
+                    *slot = nref.slot;
+                    break Some(nref.set_value(value));
+                }
             }
+        };
+        walk.walkup();
+        old_value
+    }
+
+    // re-attach the nodes detached while walking down, rotating them on
+    // the way up.
+    fn walkup(
+        node: Box<Node<K, V, A>>,
+        mut path: Path<K, V, A>,
+        m: &Metrics,
+    ) -> Box<Node<K, V, A>> {
+        let mut node = Llrb::walkuprot_23(node, m);
+        while let Some((mut parent, side)) = path.pop() {
+            parent.set_child(side, Some(node));
+            node = Llrb::walkuprot_23(parent, m);
         }
+        node
     }
 
-    fn do_delete<Q>(node: Option<Box<Node<K, V>>>, key: &Q) -> Delete<K, V>
+    fn do_delete<Q>(
+        root: &mut Option<Box<Node<K, V, A>>>,
+        key: &Q,
+        height: usize,
+        metrics: &Metrics,
+    ) -> Option<(K, V, u32)>
     where
         K: Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        let mut node = match node {
-            None => return (None, None),
-            Some(node) => node,
-        };
+        let mut walk = Walk::new(root, height, metrics);
+        let old_entry = loop {
+            let nref = match walk.node.as_deref() {
+                None => break None,
+                Some(nref) => nref,
+            };
 
-        if node.key.borrow().gt(key) {
-            if node.left.is_none() {
-                (Some(node), None)
-            } else {
-                let ok = !is_red(node.left_deref());
-                if ok && !is_red(node.left.as_ref().unwrap().left_deref()) {
-                    node = Llrb::move_red_left(node);
+            metrics.incr(Counter::Comparisons);
+            if nref.key.borrow().gt(key) {
+                if nref.left.is_none() {
+                    break None;
+                }
+                let ok = !is_red(nref.left_deref());
+                if ok && !is_red(nref.left.as_ref().unwrap().left_deref()) {
+                    walk.rebalance(Llrb::move_red_left);
                 }
-                let (left, old_value) = Llrb::do_delete(node.left.take(), key);
-                node.left = left;
-                (Some(Llrb::fixup(node)), old_value)
+                walk.push(Side::Left);
+                continue;
             }
-        } else {
-            if is_red(node.left_deref()) {
-                node = Llrb::rotate_right(node);
+
+            if is_red(nref.left_deref()) {
+                walk.rebalance(Llrb::rotate_right);
             }
 
-            if !node.key.borrow().lt(key) && node.right.is_none() {
-                return (None, Some(node.value.clone()));
+            let nref = walk.node.as_deref().unwrap();
+            metrics.incr(Counter::Comparisons);
+            if !nref.key.borrow().lt(key) && nref.right.is_none() {
+                let Node {
+                    key, value, slot, ..
+                } = *walk.node.take().unwrap();
+                break Some((key, value, slot));
             }
 
-            let ok = node.right.is_some() && !is_red(node.right_deref());
-            if ok && !is_red(node.right.as_ref().unwrap().left_deref()) {
-                node = Llrb::move_red_right(node);
+            let ok = nref.right.is_some() && !is_red(nref.right_deref());
+            if ok && !is_red(nref.right.as_ref().unwrap().left_deref()) {
+                walk.rebalance(Llrb::move_red_right);
             }
 
-            if !node.key.borrow().lt(key) {
-                // node == key
-                let (right, mut res_node) = Llrb::delete_min(node.right.take());
-                node.right = right;
-                if res_node.is_none() {
-                    panic!("do_delete(): fatal logic, call the programmer");
-                }
-                let subdel = res_node.take().unwrap();
-                let mut newnode = Box::new(subdel.clone_detach());
-                newnode.left = node.left.take();
-                newnode.right = node.right.take();
-                newnode.black = node.black;
-                (Some(Llrb::fixup(newnode)), Some(node.value.clone()))
-            } else {
-                let (right, old_value) = Llrb::do_delete(node.right.take(), key);
-                node.right = right;
-                (Some(Llrb::fixup(node)), old_value)
+            let nref = walk.node.as_deref().unwrap();
+            metrics.incr(Counter::Comparisons);
+            if !nref.key.borrow().lt(key) {
+                // nref == key, replace it with the minimum entry from its
+                // right sub-tree.
+                walk.push(Side::Right);
+                let off = walk.path.len() - 1;
+                let node = walk.node.take();
+                let (right, mut min) = Llrb::delete_min(node, &mut walk.path, metrics);
+                walk.node = right;
+                let target = &mut walk.path[off].0;
+                mem::swap(&mut target.key, &mut min.key);
+                mem::swap(&mut target.value, &mut min.value);
+                mem::swap(&mut target.slot, &mut min.slot);
+                let Node {
+                    key, value, slot, ..
+                } = min;
+                break Some((key, value, slot));
             }
-        }
+
+            walk.push(Side::Right);
+        };
+        walk.fixup();
+        old_entry
     }
 
-    fn delete_min(node: Option<Box<Node<K, V>>>) -> Delmin<K, V> {
-        if node.is_none() {
-            return (None, None);
-        }
-        let mut node = node.unwrap();
-        if node.left.is_none() {
-            return (None, Some(*node));
-        }
-        let left = node.left_deref();
-        if !is_red(left) && !is_red(left.unwrap().left_deref()) {
-            node = Llrb::move_red_left(node);
+    // detach the minimum entry from sub-tree `node`, nodes detached while
+    // walking down are pushed into `path`, to be fixed up by the caller.
+    fn delete_min(
+        node: Option<Box<Node<K, V, A>>>,
+        path: &mut Path<K, V, A>,
+        m: &Metrics,
+    ) -> Delmin<K, V, A> {
+        let mut node = match node {
+            Some(node) => node,
+            None => panic!("delete_min(): fatal logic, call the programmer"),
+        };
+        loop {
+            if node.left.is_none() {
+                break (None, *node);
+            }
+            let left = node.left_deref();
+            if !is_red(left) && !is_red(left.unwrap().left_deref()) {
+                node = Llrb::move_red_left(node, m);
+            }
+            let left = node.left.take().unwrap();
+            path.push((node, Side::Left));
+            node = left;
         }
-        let (left, old_node) = Llrb::delete_min(node.left.take());
-        node.left = left;
-        (Some(Llrb::fixup(node)), old_node)
     }
 
+    // Walk the tree, in-order, validating the LLRB invariants on every
+    // node, and collect all violations. Return the number of blacks from
+    // root to leaf and the number of nodes in the tree.
     fn validate_tree(
-        node: Option<&Node<K, V>>,
-        fromred: bool,
-        mut nb: usize,
-        depth: usize,
+        node: Option<&Node<K, V, A>>,
+        height: usize,
         stats: &mut Stats,
-    ) -> Result<usize, Error<K>> {
-        if node.is_none() {
-            stats.depths.as_mut().unwrap().sample(depth);
-            return Ok(nb);
-        }
+        violations: &mut Vec<Violation<K>>,
+    ) -> (usize, usize) {
+        let mut frames: Vec<Frame<K, V, A>> = Vec::with_capacity(height);
+        let (mut node, mut fromred, mut nb, mut depth) = (node, false, 0, 0);
+        let mut prev: Option<&K> = None;
+        let mut n_nodes = 0;
+        // path from root, to the node on top of `frames`.
+        let path = |frames: &[Frame<K, V, A>]| -> Vec<K> {
+            frames.iter().map(|frame| frame.0.key.clone()).collect()
+        };
+        loop {
+            // walk down the left spine of the sub-tree.
+            let mut blacks = loop {
+                let nref = match node {
+                    None => {
+                        stats.depths.as_mut().unwrap().sample(depth);
+                        break nb;
+                    }
+                    Some(nref) => nref,
+                };
+                let red = is_red(node);
+                if !red {
+                    nb += 1;
+                }
+                frames.push((nref, red, nb, depth, None));
+                n_nodes += 1;
+                if fromred && red {
+                    let error = Error::ConsecutiveReds;
+                    violations.push(Violation::new(path(&frames), error));
+                }
+                if is_red(nref.right_deref()) {
+                    let error = Error::RedRightLink;
+                    violations.push(Violation::new(path(&frames), error));
+                }
+                node = nref.left_deref();
+                fromred = red;
+                depth += 1;
+            };
 
-        let red = is_red(node.as_ref().map(Deref::deref));
-        if fromred && red {
-            return Err(Error::ConsecutiveReds);
-        }
-        if !red {
-            nb += 1;
-        }
-        let node = &node.as_ref().unwrap();
-        let (left, right) = (node.left_deref(), node.right_deref());
-        let lblacks = Llrb::validate_tree(left, red, nb, depth + 1, stats)?;
-        let rblacks = Llrb::validate_tree(right, red, nb, depth + 1, stats)?;
-        if lblacks != rblacks {
-            let err = format!("left: {} right: {}", lblacks, rblacks);
-            return Err(Error::UnbalancedBlacks(err));
-        }
-        if node.left.is_some() {
-            let left = node.left.as_ref().unwrap();
-            if left.key.ge(&node.key) {
-                let (lkey, parent) = (left.key.clone(), node.key.clone());
-                return Err(Error::SortError(lkey, parent));
-            }
-        }
-        if node.right.is_some() {
-            let right = node.right.as_ref().unwrap();
-            if right.key.le(&node.key) {
-                let (rkey, parent) = (right.key.clone(), node.key.clone());
-                return Err(Error::SortError(rkey, parent));
+            // walk up, until a node with un-visited right child is found.
+            loop {
+                let frame = match frames.last_mut() {
+                    None => return (blacks, n_nodes),
+                    Some(frame) => frame,
+                };
+                let (nref, red, fnb, fdepth, lblacks) = *frame;
+                match lblacks {
+                    None => {
+                        frame.4 = Some(blacks);
+                        if let Some(pkey) = prev {
+                            if pkey.ge(&nref.key) {
+                                let error = Error::SortError(pkey.clone(), nref.key.clone());
+                                violations.push(Violation::new(path(&frames), error));
+                            }
+                        }
+                        prev = Some(&nref.key);
+                        node = nref.right_deref();
+                        fromred = red;
+                        nb = fnb;
+                        depth = fdepth + 1;
+                        break;
+                    }
+                    Some(lblacks) => {
+                        let rblacks = blacks;
+                        if lblacks != rblacks {
+                            let (left, right) = (lblacks, rblacks);
+                            let error = Error::UnbalancedBlacks { left, right };
+                            violations.push(Violation::new(path(&frames), error));
+                        }
+                        blacks = lblacks;
+                        frames.pop();
+                    }
+                }
             }
         }
-        Ok(lblacks)
     }
 
     //--------- rotation routines for 2-3 algorithm ----------------
 
-    fn walkdown_rot23(node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    fn walkdown_rot23(node: Box<Node<K, V, A>>) -> Box<Node<K, V, A>> {
         node
     }
 
-    fn walkuprot_23(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    fn walkuprot_23(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
         if is_red(node.right_deref()) && !is_red(node.left_deref()) {
-            node = Llrb::rotate_left(node);
+            node = Llrb::rotate_left(node, m);
         }
         let left = node.left_deref();
         if is_red(left) && is_red(left.unwrap().left_deref()) {
-            node = Llrb::rotate_right(node);
+            node = Llrb::rotate_right(node, m);
         }
         if is_red(node.left_deref()) && is_red(node.right_deref()) {
-            Llrb::flip(node.deref_mut())
+            Llrb::flip(node.deref_mut(), m)
         }
         node
     }
@@ -467,15 +1246,18 @@ where
     //                    / \            /  \
     //                  xl   xr       left   xl
     //
-    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    fn rotate_left(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
         if is_black(node.right_deref()) {
             panic!("rotateleft(): rotating a black link ? Call the programmer");
         }
+        m.incr(Counter::RotateLeft);
         let mut x = node.right.take().unwrap();
         node.right = x.left.take();
         x.black = node.black;
         node.set_red();
+        node.repair();
         x.left = Some(node);
+        x.repair();
         x
     }
 
@@ -489,15 +1271,18 @@ where
     //         / \                                / \
     //       xl   xr                             xr  right
     //
-    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    fn rotate_right(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
         if is_black(node.left_deref()) {
             panic!("rotateright(): rotating a black link ? Call the programmer")
         }
+        m.incr(Counter::RotateRight);
         let mut x = node.left.take().unwrap();
         node.left = x.right.take();
         x.black = node.black;
         node.set_red();
+        node.repair();
         x.right = Some(node);
+        x.repair();
         x
     }
 
@@ -509,297 +1294,406 @@ where
     //     /      \              /      \
     //   left    right         left    right
     //
-    fn flip(node: &mut Node<K, V>) {
+    fn flip(node: &mut Node<K, V, A>, m: &Metrics) {
+        m.incr(Counter::Flips);
         node.left.as_mut().unwrap().toggle_link();
         node.right.as_mut().unwrap().toggle_link();
         node.toggle_link();
     }
 
-    fn fixup(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    fn fixup(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
         node = if is_red(node.right_deref()) {
-            Llrb::rotate_left(node)
+            Llrb::rotate_left(node, m)
         } else {
             node
         };
         node = {
             let left = node.left_deref();
             if is_red(left) && is_red(left.unwrap().left_deref()) {
-                Llrb::rotate_right(node)
+                Llrb::rotate_right(node, m)
             } else {
                 node
             }
         };
         if is_red(node.left_deref()) && is_red(node.right_deref()) {
-            Llrb::flip(node.deref_mut());
+            Llrb::flip(node.deref_mut(), m);
         }
         node
     }
 
-    fn move_red_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
-        Llrb::flip(node.deref_mut());
+    fn move_red_left(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
+        m.incr(Counter::MoveRedLeft);
+        Llrb::flip(node.deref_mut(), m);
         if is_red(node.right.as_ref().unwrap().left_deref()) {
-            node.right = Some(Llrb::rotate_right(node.right.take().unwrap()));
-            node = Llrb::rotate_left(node);
-            Llrb::flip(node.deref_mut());
+            node.right = Some(Llrb::rotate_right(node.right.take().unwrap(), m));
+            node = Llrb::rotate_left(node, m);
+            Llrb::flip(node.deref_mut(), m);
         }
         node
     }
 
-    fn move_red_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
-        Llrb::flip(node.deref_mut());
+    fn move_red_right(mut node: Box<Node<K, V, A>>, m: &Metrics) -> Box<Node<K, V, A>> {
+        m.incr(Counter::MoveRedRight);
+        Llrb::flip(node.deref_mut(), m);
         if is_red(node.left.as_ref().unwrap().left_deref()) {
-            node = Llrb::rotate_right(node);
-            Llrb::flip(node.deref_mut());
+            node = Llrb::rotate_right(node, m);
+            Llrb::flip(node.deref_mut(), m);
         }
         node
     }
 }
 
-fn is_red<K, V>(node: Option<&Node<K, V>>) -> bool
+// Check local invariants on a node, bounded by `low` and `high` keys
+// from its ancestors.
+fn check_node<K, V, A>(
+    nref: &Node<K, V, A>,
+    low: Option<&K>,
+    high: Option<&K>,
+    fromred: bool,
+    depth: usize,
+) -> Result<(), (usize, &'static str)>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    let above = low.is_none_or(|low| low.cmp(&nref.key) == Ordering::Less);
+    let below = high.is_none_or(|high| high.cmp(&nref.key) == Ordering::Greater);
+    if !above || !below {
+        Err((depth, "keys out of sort order"))
+    } else if fromred && !nref.is_black() {
+        Err((depth, "consecutive reds"))
+    } else if is_red(nref.right_deref()) {
+        Err((depth, "red right link"))
+    } else {
+        Ok(())
+    }
+}
+
+// Check local invariants on the left spine of a sub-tree and return the
+// number of blacks along the spine.
+fn check_spine<'a, K, V, A>(
+    mut node: Option<&'a Node<K, V, A>>,
+    low: Option<&'a K>,
+    mut high: Option<&'a K>,
+    mut fromred: bool,
+    mut depth: usize,
+) -> Result<usize, (usize, &'static str)>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    node.map_or(false, |node| !node.is_black())
+    let mut blacks = 0;
+    while let Some(nref) = node {
+        check_node(nref, low, high, fromred, depth)?;
+        if nref.is_black() {
+            blacks += 1;
+        }
+        high = Some(&nref.key);
+        fromred = !nref.is_black();
+        node = nref.left_deref();
+        depth += 1;
+    }
+    Ok(blacks)
 }
 
-fn is_black<K, V>(node: Option<&Node<K, V>>) -> bool
+pub(crate) fn is_red<K, V, A>(node: Option<&Node<K, V, A>>) -> bool
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    node.map_or(true, |node| node.is_black())
+    node.is_some_and(|node| !node.is_black())
 }
 
-pub struct Iter<'a, K, V>
+pub(crate) fn is_black<K, V, A>(node: Option<&Node<K, V, A>>) -> bool
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    paths: Option<Vec<Fragment<'a, K, V>>>,
+    node.is_none_or(|node| node.is_black())
 }
 
-impl<'a, K, V> Iterator for Iter<'a, K, V>
+pub struct Iter<'a, K, V, A = ()>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    type Item = (K, V);
+    paths: Vec<Fragment<'a, K, V, A>>,
+    live: Option<Live<'a, K>>,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut paths = match self.paths.take() {
-            Some(paths) => paths,
-            None => return None,
-        };
-        match paths.pop() {
-            None => None,
-            Some(mut path) => match (path.flag, path.nref) {
+impl<'a, K, V, A> Iter<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn next_entry(&mut self) -> Option<(K, V)> {
+        loop {
+            let path = self.paths.last_mut()?;
+            match (path.flag, path.nref) {
                 (IFlag::Left, nref) => {
                     path.flag = IFlag::Center;
-                    paths.push(path);
-                    self.paths = Some(paths);
-                    Some((nref.key.clone(), nref.value.clone()))
+                    break Some((nref.key.clone(), nref.value.clone()));
                 }
                 (IFlag::Center, nref) => {
                     path.flag = IFlag::Right;
-                    paths.push(path);
                     let rnref = nref.right_deref();
-                    self.paths = Some(build_iter(IFlag::Left, rnref, paths));
-                    self.next()
+                    build_iter(IFlag::Left, rnref, &mut self.paths);
                 }
                 (_, _) => {
-                    self.paths = Some(paths);
-                    self.next()
+                    self.paths.pop();
                 }
-            },
+            }
+        }
+    }
+}
+
+impl<'a, K, V, A> Iterator for Iter<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (k, v) = self.next_entry()?;
+            match &self.live {
+                Some(live) if !live.is_live(&k) => continue,
+                _ => break Some((k, v)),
+            }
         }
     }
 }
 
-pub struct Range<'a, K, V, R, Q>
+pub struct Range<'a, K, V, R, Q, A = ()>
 where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     R: RangeBounds<Q>,
     Q: Ord + ?Sized,
 {
     range: R,
-    paths: Option<Vec<Fragment<'a, K, V>>>,
+    paths: Vec<Fragment<'a, K, V, A>>,
+    live: Option<Live<'a, K>>,
     high: marker::PhantomData<Q>,
 }
 
-impl<'a, K, V, R, Q> Iterator for Range<'a, K, V, R, Q>
+impl<'a, K, V, R, Q, A> Range<'a, K, V, R, Q, A>
 where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     R: RangeBounds<Q>,
     Q: Ord + ?Sized,
 {
-    type Item = (K, V);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut paths = match self.paths.take() {
-            Some(paths) => paths,
-            None => return None,
-        };
-
-        let item = match paths.pop() {
-            None => None,
-            Some(mut path) => match (path.flag, path.nref) {
+    fn next_entry(&mut self) -> Option<(K, V)> {
+        loop {
+            let path = self.paths.last_mut()?;
+            match (path.flag, path.nref) {
                 (IFlag::Left, nref) => {
                     path.flag = IFlag::Center;
-                    paths.push(path);
-                    self.paths = Some(paths);
-                    Some((nref.key.clone(), nref.value.clone()))
+                    break Some((nref.key.clone(), nref.value.clone()));
                 }
                 (IFlag::Center, nref) => {
                     path.flag = IFlag::Right;
-                    paths.push(path);
                     let rnref = nref.right_deref();
-                    self.paths = Some(build_iter(IFlag::Left, rnref, paths));
-                    self.next()
+                    build_iter(IFlag::Left, rnref, &mut self.paths);
                 }
                 (_, _) => {
-                    self.paths = Some(paths);
-                    self.next()
+                    self.paths.pop();
                 }
-            },
-        };
-        match item {
-            None => None,
-            Some((k, v)) => match self.range.end_bound() {
-                Bound::Included(high) if k.borrow().le(high) => Some((k, v)),
-                Bound::Excluded(high) if k.borrow().lt(high) => Some((k, v)),
-                Bound::Unbounded => Some((k, v)),
-                Bound::Included(_) | Bound::Excluded(_) => {
-                    self.paths.take();
-                    None
+            }
+        }
+    }
+}
+
+impl<'a, K, V, R, Q, A> Iterator for Range<'a, K, V, R, Q, A>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    A: Aggregate<K, V>,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (k, v) = self.next_entry()?;
+            let ok = match self.range.end_bound() {
+                Bound::Included(high) => k.borrow().le(high),
+                Bound::Excluded(high) => k.borrow().lt(high),
+                Bound::Unbounded => true,
+            };
+            match &self.live {
+                _ if !ok => {
+                    self.paths.clear();
+                    break None;
                 }
-            },
+                Some(live) if !live.is_live(&k) => continue,
+                _ => break Some((k, v)),
+            }
         }
     }
 }
 
-pub struct Reverse<'a, K, V, R, Q>
+pub struct Reverse<'a, K, V, R, Q, A = ()>
 where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     R: RangeBounds<Q>,
     Q: Ord + ?Sized,
 {
     range: R,
-    paths: Option<Vec<Fragment<'a, K, V>>>,
+    paths: Vec<Fragment<'a, K, V, A>>,
+    live: Option<Live<'a, K>>,
     low: marker::PhantomData<Q>,
 }
 
-impl<'a, K, V, R, Q> Iterator for Reverse<'a, K, V, R, Q>
+impl<'a, K, V, R, Q, A> Reverse<'a, K, V, R, Q, A>
 where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     R: RangeBounds<Q>,
     Q: Ord + ?Sized,
 {
-    type Item = (K, V);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut paths = match self.paths.take() {
-            Some(paths) => paths,
-            None => return None,
-        };
-
-        let item = match paths.pop() {
-            None => None,
-            Some(mut path) => match (path.flag, path.nref) {
+    fn next_entry(&mut self) -> Option<(K, V)> {
+        loop {
+            let path = self.paths.last_mut()?;
+            match (path.flag, path.nref) {
                 (IFlag::Right, nref) => {
                     path.flag = IFlag::Center;
-                    paths.push(path);
-                    self.paths = Some(paths);
-                    Some((nref.key.clone(), nref.value.clone()))
+                    break Some((nref.key.clone(), nref.value.clone()));
                 }
                 (IFlag::Center, nref) => {
                     path.flag = IFlag::Left;
-                    paths.push(path);
-                    let rnref = nref.left_deref();
-                    self.paths = Some(build_iter(IFlag::Right, rnref, paths));
-                    self.next()
+                    let lnref = nref.left_deref();
+                    build_iter(IFlag::Right, lnref, &mut self.paths);
                 }
                 (_, _) => {
-                    self.paths = Some(paths);
-                    self.next()
+                    self.paths.pop();
                 }
-            },
-        };
-        match item {
-            None => None,
-            Some((k, v)) => match self.range.start_bound() {
-                Bound::Included(low) if k.borrow().ge(low) => Some((k, v)),
-                Bound::Excluded(low) if k.borrow().gt(low) => Some((k, v)),
-                Bound::Unbounded => Some((k, v)),
-                Bound::Included(_) | Bound::Excluded(_) => {
-                    self.paths.take();
-                    None
+            }
+        }
+    }
+}
+
+impl<'a, K, V, R, Q, A> Iterator for Reverse<'a, K, V, R, Q, A>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    A: Aggregate<K, V>,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (k, v) = self.next_entry()?;
+            let ok = match self.range.start_bound() {
+                Bound::Included(low) => k.borrow().ge(low),
+                Bound::Excluded(low) => k.borrow().gt(low),
+                Bound::Unbounded => true,
+            };
+            match &self.live {
+                _ if !ok => {
+                    self.paths.clear();
+                    break None;
                 }
-            },
+                Some(live) if !live.is_live(&k) => continue,
+                _ => break Some((k, v)),
+            }
         }
     }
 }
 
 /// Node corresponds to a single entry in Llrb instance.
 #[derive(Clone)]
-pub struct Node<K, V>
+pub struct Node<K, V, A = ()>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    key: K,
-    value: V,
-    black: bool,                    // store: black or red
-    left: Option<Box<Node<K, V>>>,  // store: left child
-    right: Option<Box<Node<K, V>>>, // store: right child
+    pub(crate) key: K,
+    pub(crate) value: V,
+    pub(crate) black: bool,                       // store: black or red
+    pub(crate) left: Option<Box<Node<K, V, A>>>,  // store: left child
+    pub(crate) right: Option<Box<Node<K, V, A>>>, // store: right child
+    pub(crate) agg: A,                            // store: sub-tree aggregate
+    pub(crate) slot: u32,                         // store: slot in access order
 }
 
 // Primary operations on a single node.
-impl<K, V> Node<K, V>
+impl<K, V, A> Node<K, V, A>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
     // CREATE operation
-    fn new(key: K, value: V, black: bool) -> Box<Node<K, V>> {
+    pub(crate) fn new(key: K, value: V, black: bool) -> Box<Node<K, V, A>> {
+        let agg = A::lift(&key, &value);
         Box::new(Node {
             key,
             value,
             black,
             left: None,
             right: None,
+            agg,
+            slot: NO_SLOT,
         })
     }
 
-    // clone and detach this node from the tree.
-    fn clone_detach(&self) -> Node<K, V> {
-        Node {
-            key: self.key.clone(),
-            value: self.value.clone(),
-            black: self.black,
-            left: None,
-            right: None,
+    #[inline]
+    fn set_child(&mut self, side: Side, child: Option<Box<Node<K, V, A>>>) {
+        match side {
+            Side::Left => self.left = child,
+            Side::Right => self.right = child,
+        }
+        self.repair();
+    }
+
+    // re-compute the aggregate for this sub-tree, from its children.
+    #[inline]
+    pub(crate) fn repair(&mut self) {
+        let mut agg = A::lift(&self.key, &self.value);
+        if let Some(left) = self.left_deref() {
+            agg = left.agg.combine(&agg);
+        }
+        if let Some(right) = self.right_deref() {
+            agg = agg.combine(&right.agg);
         }
+        self.agg = agg;
     }
 
     #[inline]
-    fn left_deref(&self) -> Option<&Node<K, V>> {
-        self.left.as_ref().map(Deref::deref)
+    pub(crate) fn left_deref(&self) -> Option<&Node<K, V, A>> {
+        self.left.as_deref()
     }
 
     #[inline]
-    fn right_deref(&self) -> Option<&Node<K, V>> {
-        self.right.as_ref().map(Deref::deref)
+    pub(crate) fn right_deref(&self) -> Option<&Node<K, V, A>> {
+        self.right.as_deref()
     }
 
     // prepend operation, equivalent to SET / INSERT / UPDATE
     #[inline]
-    fn set_value(&mut self, value: V) {
-        self.value = value
+    fn set_value(&mut self, value: V) -> V {
+        let old_value = mem::replace(&mut self.value, value);
+        self.repair();
+        old_value
     }
 
     #[inline]
@@ -829,12 +1723,19 @@ where
 ///
 /// * To get partial but quick statistics via [`Llrb::stats`] method.
 /// * To get full statisics via [`Llrb::validate`] method.
+///
+/// Statistics can be exported as JSON, via [`Stats::json`], in
+/// prometheus text format, via [`Stats::prometheus`], and with the
+/// `serde` feature, via serde's `Serialize`.
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Stats {
     entries: usize, // number of entries in the tree.
     node_size: usize,
     blacks: Option<usize>,
     depths: Option<Depth>,
+    expired: usize,
+    counters: Option<Counters>,
 }
 
 impl Stats {
@@ -844,9 +1745,21 @@ impl Stats {
             node_size,
             blacks: Default::default(),
             depths: Default::default(),
+            expired: Default::default(),
+            counters: Default::default(),
         }
     }
 
+    #[inline]
+    fn set_counters(&mut self, counters: Option<Counters>) {
+        self.counters = counters
+    }
+
+    #[inline]
+    fn set_expired(&mut self, expired: usize) {
+        self.expired = expired
+    }
+
     #[inline]
     fn set_blacks(&mut self, blacks: usize) {
         self.blacks = Some(blacks)
@@ -888,127 +1801,294 @@ impl Stats {
         self.blacks
     }
 
+    /// Return number of entries removed from [`Llrb`] instance after
+    /// their time-to-live lapsed.
+    #[inline]
+    pub fn expired(&self) -> usize {
+        self.expired
+    }
+
+    /// Return counters on operations and tree restructuring, if this
+    /// package is compiled with the `metrics` feature. Refer to
+    /// [`Llrb::reset_counters`].
+    pub fn counters(&self) -> Option<Counters> {
+        self.counters.clone()
+    }
+
     /// Return [`Depth`] statistics.
     pub fn depths(&self) -> Option<Depth> {
-        if self.depths.as_ref().unwrap().samples() == 0 {
+        let depths = self.depths.as_ref()?;
+        if depths.samples() == 0 {
             None
         } else {
-            self.depths.clone()
+            Some(depths.clone())
+        }
+    }
+
+    /// Convert statistics to JSON format. Fields that are not computed,
+    /// like `blacks` and `depths` for quick statistics, are null.
+    pub fn json(&self) -> String {
+        let blacks = match self.blacks {
+            Some(blacks) => blacks.to_string(),
+            None => "null".to_string(),
+        };
+        let depths = match self.depths.as_ref() {
+            Some(depths) => depths.json(),
+            None => "null".to_string(),
+        };
+        let counters = match self.counters.as_ref() {
+            Some(counters) => {
+                let iter = counters.to_fields().into_iter();
+                let fields: Vec<String> = iter.map(|(n, c)| format!("\"{}\": {}", n, c)).collect();
+                "{".to_string() + fields.join(", ").as_str() + "}"
+            }
+            None => "null".to_string(),
+        };
+        let strs = [
+            format!("\"entries\": {}", self.entries),
+            format!("\"node_size\": {}", self.node_size),
+            format!("\"blacks\": {}", blacks),
+            format!("\"depths\": {}", depths),
+            format!("\"expired\": {}", self.expired),
+            format!("\"counters\": {}", counters),
+        ];
+        "{".to_string() + strs.join(", ").as_str() + "}"
+    }
+
+    /// Convert statistics to prometheus text format, labelling each
+    /// sample with the `index` name. Leaf-node depths are exported as a
+    /// summary, when available.
+    pub fn prometheus(&self, index: &str) -> String {
+        let mut index_label = String::new();
+        for ch in index.chars() {
+            match ch {
+                '\\' => index_label.push_str("\\\\"),
+                '"' => index_label.push_str("\\\""),
+                '\n' => index_label.push_str("\\n"),
+                ch => index_label.push(ch),
+            }
+        }
+        let labels = format!("index=\"{}\"", index_label);
+
+        let mut out = String::new();
+        let mut metric = |name: &str, typ: &str, help: &str, value: usize| {
+            out.push_str(&format!("# HELP {} {}\n", name, help));
+            out.push_str(&format!("# TYPE {} {}\n", name, typ));
+            out.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+        };
+        let help = "Number of entries in the index.";
+        metric("llrb_entries", "gauge", help, self.entries);
+        let help = "Size of a tree node, in bytes.";
+        metric("llrb_node_size_bytes", "gauge", help, self.node_size);
+        if let Some(blacks) = self.blacks {
+            let help = "Number of blacks from root to leaf.";
+            metric("llrb_blacks", "gauge", help, blacks);
         }
+        let help = "Number of entries removed after their time-to-live.";
+        metric("llrb_expired_total", "counter", help, self.expired);
+        for (name, count) in self.counters.iter().flat_map(|c| c.to_fields()) {
+            let help = format!("Counter for {}, refer to llrb_index::Counters.", name);
+            metric(
+                &format!("llrb_{}_total", name),
+                "counter",
+                &help,
+                count as usize,
+            );
+        }
+        if let Some(depths) = self.depths() {
+            depths.prometheus(&labels, &mut out);
+        }
+        out
+    }
+}
+
+/// Violation of an LLRB invariant, found while validating [`Llrb`]
+/// tree, refer to [`Llrb::validate_report`].
+#[derive(Debug, PartialEq)]
+pub struct Violation<K>
+where
+    K: Clone + Ord,
+{
+    path: Vec<K>,
+    error: Error<K>,
+}
+
+impl<K> Violation<K>
+where
+    K: Clone + Ord,
+{
+    fn new(path: Vec<K>, error: Error<K>) -> Violation<K> {
+        Violation { path, error }
+    }
+
+    /// Return keys along the path from root to the offending node. Empty
+    /// for violations on the tree as a whole.
+    pub fn path(&self) -> &[K] {
+        &self.path
+    }
+
+    /// Return the invariant violated.
+    pub fn error(&self) -> &Error<K> {
+        &self.error
+    }
+}
+
+/// Outcome of validating [`Llrb`] tree, refer to
+/// [`Llrb::validate_report`].
+pub struct ValidationReport<K>
+where
+    K: Clone + Ord,
+{
+    stats: Stats,
+    violations: Vec<Violation<K>>,
+}
+
+impl<K> ValidationReport<K>
+where
+    K: Clone + Ord,
+{
+    /// Check whether the tree is free of violations.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
     }
+
+    /// Return full statistics on the tree, refer to [`Stats`].
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Return all violations, in the order they were found.
+    pub fn violations(&self) -> &[Violation<K>] {
+        &self.violations
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Side {
+    Left,
+    Right,
 }
 
 #[derive(Copy, Clone)]
-enum IFlag {
+pub(crate) enum IFlag {
     Left,
     Center,
     Right,
 }
 
-struct Fragment<'a, K, V>
+pub(crate) struct Fragment<'a, K, V, A = ()>
 where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    flag: IFlag,
-    nref: &'a Node<K, V>,
+    pub(crate) flag: IFlag,
+    pub(crate) nref: &'a Node<K, V, A>,
 }
 
-fn build_iter<'a, K, V>(
+pub(crate) fn build_iter<'a, K, V, A>(
     flag: IFlag,
-    nref: Option<&'a Node<K, V>>, // subtree
-    mut paths: Vec<Fragment<'a, K, V>>,
-) -> Vec<Fragment<'a, K, V>>
-where
+    nref: Option<&'a Node<K, V, A>>, // subtree
+    paths: &mut Vec<Fragment<'a, K, V, A>>,
+) where
     K: Clone + Ord,
     V: Clone,
+    A: Aggregate<K, V>,
 {
-    match nref {
-        None => paths,
-        Some(nref) => {
-            let item = Fragment { flag, nref };
-            let nref = match flag {
-                IFlag::Left => nref.left_deref(),
-                IFlag::Right => nref.right_deref(),
-                IFlag::Center => unreachable!(),
-            };
-            paths.push(item);
-            build_iter(flag, nref, paths)
-        }
+    let mut nref = nref;
+    while let Some(node) = nref {
+        paths.push(Fragment { flag, nref: node });
+        nref = match flag {
+            IFlag::Left => node.left_deref(),
+            IFlag::Right => node.right_deref(),
+            IFlag::Center => unreachable!(),
+        };
     }
 }
 
-fn find_start<'a, K, V, Q>(
-    nref: Option<&'a Node<K, V>>,
+pub(crate) fn find_start<'a, K, V, Q, A>(
+    nref: Option<&'a Node<K, V, A>>,
     low: &Q,
     incl: bool,
-    mut paths: Vec<Fragment<'a, K, V>>,
-) -> Vec<Fragment<'a, K, V>>
-where
+    paths: &mut Vec<Fragment<'a, K, V, A>>,
+) where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     Q: Ord + ?Sized,
 {
-    match nref {
-        None => paths,
-        Some(nref) => {
-            let cmp = nref.key.borrow().cmp(low);
-            let flag = match cmp {
-                Ordering::Less => IFlag::Right,
-                Ordering::Equal if incl => IFlag::Left,
-                Ordering::Equal => IFlag::Center,
-                Ordering::Greater => IFlag::Left,
-            };
-            paths.push(Fragment { flag, nref });
-            match cmp {
-                Ordering::Less => {
-                    let nref = nref.right_deref();
-                    find_start(nref, low, incl, paths)
-                }
-                Ordering::Equal => paths,
-                Ordering::Greater => {
-                    let nref = nref.left_deref();
-                    find_start(nref, low, incl, paths)
-                }
-            }
-        }
+    let mut nref = nref;
+    while let Some(node) = nref {
+        let cmp = node.key.borrow().cmp(low);
+        let flag = match cmp {
+            Ordering::Less => IFlag::Right,
+            Ordering::Equal if incl => IFlag::Left,
+            Ordering::Equal => IFlag::Center,
+            Ordering::Greater => IFlag::Left,
+        };
+        paths.push(Fragment { flag, nref: node });
+        nref = match cmp {
+            Ordering::Less => node.right_deref(),
+            Ordering::Equal => None,
+            Ordering::Greater => node.left_deref(),
+        };
+    }
+}
+
+// Whether key is above the low bound of range.
+pub(crate) fn is_above<K, Q, R>(range: &R, key: &K) -> bool
+where
+    K: Borrow<Q>,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    match range.start_bound() {
+        Bound::Included(low) => key.borrow().ge(low),
+        Bound::Excluded(low) => key.borrow().gt(low),
+        Bound::Unbounded => true,
+    }
+}
+
+// Whether key is below the high bound of range.
+pub(crate) fn is_below<K, Q, R>(range: &R, key: &K) -> bool
+where
+    K: Borrow<Q>,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    match range.end_bound() {
+        Bound::Included(high) => key.borrow().le(high),
+        Bound::Excluded(high) => key.borrow().lt(high),
+        Bound::Unbounded => true,
     }
 }
 
-fn find_end<'a, K, V, Q>(
-    nref: Option<&'a Node<K, V>>,
+pub(crate) fn find_end<'a, K, V, Q, A>(
+    nref: Option<&'a Node<K, V, A>>,
     high: &Q,
     incl: bool,
-    mut paths: Vec<Fragment<'a, K, V>>,
-) -> Vec<Fragment<'a, K, V>>
-where
+    paths: &mut Vec<Fragment<'a, K, V, A>>,
+) where
     K: Clone + Ord + Borrow<Q>,
     V: Clone,
+    A: Aggregate<K, V>,
     Q: Ord + ?Sized,
 {
-    match nref {
-        None => paths,
-        Some(nref) => {
-            let cmp = nref.key.borrow().cmp(high);
-            let flag = match cmp {
-                Ordering::Less => IFlag::Right,
-                Ordering::Equal if incl => IFlag::Right,
-                Ordering::Equal => IFlag::Center,
-                Ordering::Greater => IFlag::Left,
-            };
-            paths.push(Fragment { flag, nref });
-            match cmp {
-                Ordering::Less => {
-                    let nref = nref.right_deref();
-                    find_end(nref, high, incl, paths)
-                }
-                Ordering::Equal => paths,
-                Ordering::Greater => {
-                    let nref = nref.left_deref();
-                    find_end(nref, high, incl, paths)
-                }
-            }
-        }
+    let mut nref = nref;
+    while let Some(node) = nref {
+        let cmp = node.key.borrow().cmp(high);
+        let flag = match cmp {
+            Ordering::Less => IFlag::Right,
+            Ordering::Equal if incl => IFlag::Right,
+            Ordering::Equal => IFlag::Center,
+            Ordering::Greater => IFlag::Left,
+        };
+        paths.push(Fragment { flag, nref: node });
+        nref = match cmp {
+            Ordering::Less => node.right_deref(),
+            Ordering::Equal => None,
+            Ordering::Greater => node.left_deref(),
+        };
     }
 }
 
@@ -1,11 +1,17 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::ops::Bound;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::panic;
+use std::time::{Duration, Instant};
 
 use rand::prelude::random;
-use rand::{rngs::SmallRng, SeedableRng};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 use crate::error::Error;
+use crate::evict::{Capacity, Eviction};
 use crate::llrb::Llrb;
+use crate::test_util::make_seed;
 
 #[test]
 fn test_id() {
@@ -30,6 +36,129 @@ fn test_stats() {
     assert_eq!(stats.node_size(), 72);
 }
 
+#[test]
+fn test_stats_export() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    let stats = llrb.stats();
+    assert!(stats.depths().is_none());
+    let value: serde_json::Value = serde_json::from_str(&stats.json()).unwrap();
+    assert_eq!(value["entries"], 0);
+    assert_eq!(value["node_size"], 40);
+    assert!(value["blacks"].is_null());
+    assert!(value["depths"].is_null());
+    assert_eq!(value["expired"], 0);
+
+    (0..1000).for_each(|key| {
+        llrb.set(key, key);
+    });
+    let stats = llrb.validate().unwrap();
+    let depths = stats.depths().unwrap();
+    let value: serde_json::Value = serde_json::from_str(&stats.json()).unwrap();
+    assert_eq!(value["entries"], 1000);
+    assert_eq!(value["blacks"], stats.blacks().unwrap());
+    assert_eq!(value["depths"]["samples"], depths.samples());
+    assert_eq!(value["depths"]["max"], depths.max());
+    assert_eq!(
+        value["depths"]["percentiles"]["50"],
+        depths.percentile(50.0).unwrap()
+    );
+    assert_eq!(
+        value["depths"]["percentiles"]["99.9"],
+        depths.percentile(99.9).unwrap()
+    );
+    #[cfg(feature = "serde")]
+    assert_eq!(serde_json::to_value(&stats).unwrap(), value);
+
+    let text = stats.prometheus("test\"llrb");
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.contains(&"# TYPE llrb_entries gauge"));
+    assert!(lines.contains(&"llrb_entries{index=\"test\\\"llrb\"} 1000"));
+    let line = format!(
+        "llrb_blacks{{index=\"test\\\"llrb\"}} {}",
+        stats.blacks().unwrap()
+    );
+    assert!(lines.contains(&line.as_str()));
+    assert!(lines.contains(&"# TYPE llrb_leaf_depth summary"));
+    let line = format!(
+        "llrb_leaf_depth{{index=\"test\\\"llrb\",quantile=\"0.999\"}} {}",
+        depths.percentile(99.9).unwrap()
+    );
+    assert!(lines.contains(&line.as_str()));
+    let line = format!(
+        "llrb_leaf_depth_count{{index=\"test\\\"llrb\"}} {}",
+        depths.samples()
+    );
+    assert!(lines.contains(&line.as_str()));
+    // every sample is a metric name, with labels, followed by a value.
+    for line in lines.into_iter().filter(|l| !l.starts_with('#')) {
+        let (metric, value) = line.rsplit_once(' ').unwrap();
+        assert!(
+            metric.starts_with("llrb_") && metric.ends_with('}'),
+            "{}",
+            line
+        );
+        value.parse::<f64>().unwrap();
+    }
+}
+
+#[test]
+fn test_counters() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    if !cfg!(feature = "metrics") {
+        assert_eq!(llrb.stats().counters(), None);
+        return;
+    }
+
+    let counters = llrb.stats().counters().unwrap();
+    assert_eq!(counters.comparisons_per_lookup(), 0.0);
+    assert!(counters.to_fields().iter().all(|(_, c)| *c == 0));
+
+    (0..1000).for_each(|key| {
+        llrb.set(key, key);
+    });
+    (0..100).for_each(|key| {
+        assert!(llrb.create(key, key).is_err());
+    });
+    (1000..1100).for_each(|key| {
+        assert!(llrb.create(key, key).is_ok());
+    });
+    (0..500).for_each(|key| {
+        llrb.get(&key).unwrap();
+    });
+    (0..300).for_each(|key| {
+        llrb.delete(&key).unwrap();
+    });
+
+    let counters = llrb.validate().unwrap().counters().unwrap();
+    assert_eq!(counters.sets(), 1000);
+    assert_eq!(counters.failed_creates(), 100);
+    assert_eq!(counters.creates(), 100);
+    assert_eq!(counters.gets(), 500);
+    assert_eq!(counters.deletes(), 300);
+    // sequential inserts rotate left and flip, deletes move red links.
+    assert!(counters.rotate_left() > 0);
+    assert!(counters.flips() > 0);
+    assert!(counters.move_red_left() + counters.move_red_right() > 0);
+    let per_lookup = counters.comparisons_per_lookup();
+    assert!(
+        per_lookup > 1.0 && per_lookup < 2.0 * 11.0,
+        "{}",
+        per_lookup
+    );
+
+    let value: serde_json::Value = serde_json::from_str(&llrb.stats().json()).unwrap();
+    assert_eq!(value["counters"]["gets"], 500);
+    let text = llrb.stats().prometheus("test-llrb");
+    assert!(text.contains("llrb_gets_total{index=\"test-llrb\"} 500\n"));
+
+    let mut cloned = llrb.clone();
+    assert_eq!(cloned.stats().counters().unwrap(), counters);
+    cloned.reset_counters();
+    let counters = cloned.stats().counters().unwrap();
+    assert!(counters.to_fields().iter().all(|(_, c)| *c == 0));
+    assert_eq!(llrb.stats().counters().unwrap().gets(), 500);
+}
+
 #[test]
 fn test_create() {
     let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
@@ -192,6 +321,7 @@ fn test_delete() {
 }
 
 #[test]
+#[allow(clippy::manual_range_contains)]
 fn test_random() {
     let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
     let mut rng = SmallRng::from_seed(make_seed().to_le_bytes());
@@ -417,11 +547,300 @@ fn test_crud() {
     }
 }
 
-fn make_seed() -> u128 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos()
+#[test]
+fn test_small_stack() {
+    let handle = std::thread::Builder::new()
+        .stack_size(32 * 1024)
+        .spawn(|| {
+            let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+            for key in 0..100_000 {
+                assert!(llrb.set(key, key).is_none());
+            }
+            for key in (0..100_000).step_by(3) {
+                assert_eq!(llrb.delete(&key), Some(key));
+            }
+            assert!(llrb.create(0, 0).is_ok());
+            assert!(llrb.validate().is_ok());
+            assert_eq!(llrb.iter().count(), llrb.len());
+            assert_eq!(llrb.range(1000..2000).count(), 667);
+            assert_eq!(llrb.reverse(1000..2000).count(), 667);
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_ttl() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    let hour = Duration::from_secs(3600);
+
+    for key in 0..100 {
+        let ttl = if key % 2 == 0 {
+            Duration::from_secs(0)
+        } else {
+            hour
+        };
+        assert!(llrb.set_with_ttl(key, key * 10, ttl).is_none());
+    }
+    assert!(llrb.set(100, 1000).is_none());
+    assert_eq!(llrb.len(), 101);
+
+    // expired entries are hidden from reads.
+    assert_eq!(llrb.get(&10), None);
+    assert_eq!(llrb.get(&11), Some(110));
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    let refkeys: Vec<i64> = (0..101).filter(|k| k % 2 == 1 || *k == 100).collect();
+    assert_eq!(keys, refkeys);
+    let keys: Vec<i64> = llrb.range(10..=20).map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![11, 13, 15, 17, 19]);
+    let keys: Vec<i64> = llrb.reverse(10..=20).map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![19, 17, 15, 13, 11]);
+
+    // write operations treat expired entries as absent.
+    assert_eq!(llrb.create(2, 21), Ok(()));
+    assert_eq!(llrb.set(4, 41), None);
+    assert_eq!(llrb.delete(&6), None);
+    assert_eq!(llrb.set(2, 22), Some(21));
+    assert_eq!(llrb.stats().expired(), 3);
+
+    // expire incrementally.
+    let now = Instant::now();
+    assert_eq!(llrb.expire(now, 10), 10);
+    assert_eq!(llrb.expire(now, 100), 37);
+    assert_eq!(llrb.expire(now, 100), 0);
+    assert_eq!(llrb.len(), 53);
+    assert_eq!(llrb.stats().expired(), 50);
+    assert!(llrb.validate().is_ok());
+
+    // plain set clears the deadline.
+    assert_eq!(llrb.set(11, 111), Some(110));
+    assert_eq!(llrb.expire(now + hour, 100), 49);
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![2, 4, 11, 100]);
+    assert_eq!(llrb.stats().expired(), 99);
+}
+
+#[test]
+fn test_validate_report() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..7).for_each(|key| {
+        llrb.set(key * 10, key);
+    });
+    let report = llrb.validate_report();
+    assert!(report.is_ok());
+    assert_eq!(report.stats().entries(), 7);
+    assert_eq!(report.violations().len(), 0);
+
+    // tree shape, for 7 entries, is a perfect 2-3 tree of blacks.
+    {
+        let root = llrb.root.as_mut().unwrap();
+        assert_eq!(root.key, 30);
+        root.black = false; // red root.
+        let left = root.left.as_mut().unwrap();
+        left.key = 35; // unsorted, against its right child.
+        let right = root.right.as_mut().unwrap();
+        let rr = right.right.as_mut().unwrap();
+        rr.black = false; // red right link, also unbalanced.
+    }
+    llrb.n_count = 8;
+
+    let report = llrb.validate_report();
+    assert!(!report.is_ok());
+    let violations: Vec<(Vec<i64>, &Error<i64>)> = report
+        .violations()
+        .iter()
+        .map(|v| (v.path().to_vec(), v.error()))
+        .collect();
+    assert_eq!(
+        violations,
+        vec![
+            (vec![30], &Error::RedRoot),
+            (vec![30, 35, 20], &Error::SortError(35, 20)),
+            (vec![30, 50], &Error::RedRightLink),
+            (vec![30, 50], &Error::UnbalancedBlacks { left: 2, right: 1 }),
+            (vec![], &Error::CountMismatch { count: 8, nodes: 7 }),
+        ]
+    );
+    assert_eq!(llrb.validate().err(), Some(Error::RedRoot));
+}
+
+#[test]
+fn test_paranoid() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed:{}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    assert_eq!(llrb.is_paranoid(), cfg!(feature = "paranoid"));
+    llrb.set_paranoid(true);
+    assert!(llrb.is_paranoid());
+    for _ in 0..10_000 {
+        let key: i64 = rng.gen_range(0, 1000);
+        match rng.gen_range(0, 3) {
+            0 => {
+                llrb.create(key, key).ok();
+            }
+            1 => {
+                llrb.set(key, key);
+            }
+            _ => {
+                llrb.delete(&key);
+            }
+        }
+    }
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_paranoid_bad_ord() {
+    thread_local!(static REVERSED: Cell<bool> = const { Cell::new(false) });
+
+    // ordering that flips, once REVERSED is set.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Bad(i64);
+
+    impl PartialOrd for Bad {
+        fn partial_cmp(&self, other: &Bad) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Bad {
+        fn cmp(&self, other: &Bad) -> Ordering {
+            match REVERSED.with(Cell::get) {
+                true => other.0.cmp(&self.0),
+                false => self.0.cmp(&other.0),
+            }
+        }
+    }
+
+    let mut llrb: Llrb<Bad, i64> = Llrb::new("test-llrb");
+    llrb.set_paranoid(true);
+    (0..100).for_each(|key| {
+        llrb.set(Bad(key), key);
+    });
+    REVERSED.with(|reversed| reversed.set(true));
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        llrb.set(Bad(1000), 1000);
+    }));
+    let msg = res.unwrap_err().downcast::<String>().unwrap();
+    assert!(
+        msg.starts_with("test-llrb: set() broke the tree"),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_panic_safety() {
+    thread_local!(static COUNTDOWN: Cell<usize> = const { Cell::new(usize::MAX) });
+
+    // panic once COUNTDOWN, for compare and clone, reaches zero.
+    fn tick() {
+        let n = COUNTDOWN.with(|c| c.replace(c.get().saturating_sub(1)));
+        if n == 0 {
+            panic!("countdown");
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Flaky(i64);
+
+    impl Clone for Flaky {
+        fn clone(&self) -> Flaky {
+            tick();
+            Flaky(self.0)
+        }
+    }
+
+    impl PartialOrd for Flaky {
+        fn partial_cmp(&self, other: &Flaky) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Flaky {
+        fn cmp(&self, other: &Flaky) -> Ordering {
+            tick();
+            self.0.cmp(&other.0)
+        }
+    }
+
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed:{}", seed);
+
+    let (capacity, policy) = (Capacity::Entries(500), Eviction::Lru);
+    let mut llrb: Llrb<Flaky, i64> = Llrb::new_bounded("test-llrb", capacity, policy);
+    let mut refns: BTreeMap<i64, i64> = BTreeMap::new();
+    let hour = Duration::from_secs(3600);
+    let mut n_panics = 0;
+    // live clone of the instance, along with its entries when cloned.
+    let mut snapshot = (llrb.clone(), vec![]);
+    for i in 0..10_000 {
+        if i % 100 == 0 {
+            let entries: Vec<(i64, i64)> = llrb.iter().map(|(k, v)| (k.0, v)).collect();
+            snapshot = (llrb.clone(), entries);
+        }
+        let key: i64 = rng.gen_range(0, 1000);
+        let op = rng.gen_range(0, 4);
+        COUNTDOWN.with(|c| c.set(rng.gen_range(0, 40)));
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| match op {
+            0 => llrb.create(Flaky(key), key).is_ok(),
+            1 => llrb.set(Flaky(key), key).is_some(),
+            2 => llrb.set_with_ttl(Flaky(key), key, hour).is_some(),
+            _ => llrb.delete(&Flaky(key)).is_some(),
+        }));
+        COUNTDOWN.with(|c| c.set(usize::MAX));
+        match (op, res) {
+            // a write that panicked might, or might not, have taken effect.
+            (_, Err(_)) => {
+                n_panics += 1;
+                let stats = llrb.validate().unwrap();
+                assert_eq!(stats.entries(), llrb.len());
+                assert_eq!(llrb.iter().count(), llrb.len());
+                match llrb.get(&Flaky(key)) {
+                    Some(value) => refns.insert(key, value),
+                    None => refns.remove(&key),
+                };
+            }
+            (0, Ok(false)) => (),
+            (0..=2, Ok(_)) => {
+                refns.insert(key, key);
+            }
+            (_, Ok(_)) => {
+                refns.remove(&key);
+            }
+        }
+        for (key, _) in llrb.drain_evicted() {
+            refns.remove(&key.0);
+        }
+        assert_eq!(llrb.len(), refns.len());
+    }
+    assert!(n_panics > 0);
+    let stats = llrb.validate().unwrap();
+    assert_eq!(stats.entries(), llrb.iter().count());
+    let entries: Vec<(i64, i64)> = llrb.iter().map(|(k, v)| (k.0, v)).collect();
+    let refs: Vec<(i64, i64)> = refns.into_iter().collect();
+    assert_eq!(entries, refs);
+
+    // clone is left untouched by the writes that panicked.
+    let (snapshot, refs) = snapshot;
+    assert!(snapshot.validate().is_ok());
+    assert_eq!(snapshot.iter().count(), snapshot.len());
+    let entries: Vec<(i64, i64)> = snapshot.iter().map(|(k, v)| (k.0, v)).collect();
+    assert_eq!(entries, refs);
+
+    // book-keeping shall be left consistent with the tree.
+    let n = llrb.len();
+    let n_expired = llrb.expire(Instant::now() + hour * 2, usize::MAX);
+    assert_eq!(llrb.len(), n - n_expired);
+    for key in 1000..2000 {
+        llrb.set(Flaky(key), key);
+    }
+    assert_eq!(llrb.len(), 500);
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k.0).collect();
+    assert_eq!(keys, (1500..2000).collect::<Vec<i64>>());
 }
 
 include!("./ref_test.rs");
@@ -0,0 +1,180 @@
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+// Operations and restructuring counted on Llrb instance.
+#[derive(Clone, Copy)]
+pub(crate) enum Counter {
+    Gets = 0,
+    Sets,
+    Creates,
+    FailedCreates,
+    Deletes,
+    RotateLeft,
+    RotateRight,
+    Flips,
+    MoveRedLeft,
+    MoveRedRight,
+    Comparisons,
+}
+
+const N_COUNTERS: usize = 11;
+
+const NAMES: [&str; N_COUNTERS] = [
+    "gets",
+    "sets",
+    "creates",
+    "failed_creates",
+    "deletes",
+    "rotate_left",
+    "rotate_right",
+    "flips",
+    "move_red_left",
+    "move_red_right",
+    "comparisons",
+];
+
+// Metrics are updated by the tree, as it is read and restructured, only
+// with the `metrics` feature. Otherwise it is zero sized and counting
+// is a no-op.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    #[cfg(feature = "metrics")]
+    counters: [AtomicU64; N_COUNTERS],
+}
+
+impl Clone for Metrics {
+    fn clone(&self) -> Metrics {
+        let metrics = Metrics::default();
+        #[cfg(feature = "metrics")]
+        for (dst, src) in metrics.counters.iter().zip(self.counters.iter()) {
+            dst.store(src.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        metrics
+    }
+}
+
+impl Metrics {
+    #[inline]
+    #[allow(unused_variables)]
+    pub(crate) fn incr(&self, counter: Counter) {
+        #[cfg(feature = "metrics")]
+        self.counters[counter as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Metrics::default();
+    }
+
+    // Return a snapshot of the counters, if they are maintained.
+    pub(crate) fn to_counters(&self) -> Option<Counters> {
+        #[cfg(feature = "metrics")]
+        {
+            let mut values = [0; N_COUNTERS];
+            for (value, counter) in values.iter_mut().zip(self.counters.iter()) {
+                *value = counter.load(Ordering::Relaxed);
+            }
+            Some(Counters { values })
+        }
+        #[cfg(not(feature = "metrics"))]
+        None
+    }
+}
+
+/// Counters on operations and tree restructuring, for
+/// [`Llrb`](crate::Llrb) instance compiled with the `metrics` feature.
+/// Counted since the instance was created, or since
+/// [`Llrb::reset_counters`](crate::Llrb::reset_counters).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Counters {
+    values: [u64; N_COUNTERS],
+}
+
+impl Counters {
+    /// Return number of get operations.
+    pub fn gets(&self) -> u64 {
+        self.values[Counter::Gets as usize]
+    }
+
+    /// Return number of set operations.
+    pub fn sets(&self) -> u64 {
+        self.values[Counter::Sets as usize]
+    }
+
+    /// Return number of create operations that succeeded.
+    pub fn creates(&self) -> u64 {
+        self.values[Counter::Creates as usize]
+    }
+
+    /// Return number of create operations that failed, because the key
+    /// was already present.
+    pub fn failed_creates(&self) -> u64 {
+        self.values[Counter::FailedCreates as usize]
+    }
+
+    /// Return number of delete operations, including entries removed
+    /// by eviction and expiry.
+    pub fn deletes(&self) -> u64 {
+        self.values[Counter::Deletes as usize]
+    }
+
+    /// Return number of left rotations.
+    pub fn rotate_left(&self) -> u64 {
+        self.values[Counter::RotateLeft as usize]
+    }
+
+    /// Return number of right rotations.
+    pub fn rotate_right(&self) -> u64 {
+        self.values[Counter::RotateRight as usize]
+    }
+
+    /// Return number of color flips.
+    pub fn flips(&self) -> u64 {
+        self.values[Counter::Flips as usize]
+    }
+
+    /// Return number of times a red link was moved left, while deleting.
+    pub fn move_red_left(&self) -> u64 {
+        self.values[Counter::MoveRedLeft as usize]
+    }
+
+    /// Return number of times a red link was moved right, while deleting.
+    pub fn move_red_right(&self) -> u64 {
+        self.values[Counter::MoveRedRight as usize]
+    }
+
+    /// Return number of key comparisons, while looking up keys for get,
+    /// set, create and delete operations.
+    pub fn comparisons(&self) -> u64 {
+        self.values[Counter::Comparisons as usize]
+    }
+
+    /// Return the average number of key comparisons per lookup. Return
+    /// 0.0 if there was no lookup.
+    pub fn comparisons_per_lookup(&self) -> f64 {
+        let lookups = self.gets() + self.sets() + self.creates();
+        let lookups = lookups + self.failed_creates() + self.deletes();
+        match lookups {
+            0 => 0.0,
+            lookups => self.comparisons() as f64 / lookups as f64,
+        }
+    }
+
+    /// Return all counters as tuple of (name, count).
+    pub fn to_fields(&self) -> Vec<(&'static str, u64)> {
+        let iter = NAMES.iter().cloned();
+        iter.zip(self.values.iter().cloned()).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Counters {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.to_fields())
+    }
+}
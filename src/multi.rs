@@ -0,0 +1,242 @@
+use std::{borrow::Borrow, cmp::Ordering, ops::RangeBounds, vec};
+
+use crate::error::Error;
+use crate::llrb::{Iter, Llrb, Range, Reverse, Stats};
+
+/// LlrbMulti is an ordered multi-map, where each key can map to more
+/// than one value, backed by ``Llrb<K, Vec<V>>``.
+///
+/// Values for a key are kept in insertion order, or in sort order if
+/// created via [`LlrbMulti::new_sorted`]. The same value can be
+/// inserted more than once for a key.
+#[derive(Clone)]
+pub struct LlrbMulti<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    llrb: Llrb<K, Vec<V>>,
+    order: Option<fn(&V, &V) -> Ordering>,
+    n_values: usize, // number of (key, value) pairs.
+}
+
+impl<K, V> Extend<(K, V)> for LlrbMulti<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        iter.into_iter()
+            .for_each(|(key, value)| self.insert(key, value));
+    }
+}
+
+impl<K, V> LlrbMulti<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Create an empty multi-map, identified by `name`, that keeps values
+    /// for a key in insertion order.
+    pub fn new<S>(name: S) -> LlrbMulti<K, V>
+    where
+        S: AsRef<str>,
+    {
+        LlrbMulti {
+            llrb: Llrb::new(name),
+            order: None,
+            n_values: 0,
+        }
+    }
+
+    /// Create an empty multi-map, identified by `name`, that keeps values
+    /// for a key in sort order. Equal values are kept in insertion order.
+    pub fn new_sorted<S>(name: S) -> LlrbMulti<K, V>
+    where
+        S: AsRef<str>,
+        V: Ord,
+    {
+        LlrbMulti {
+            llrb: Llrb::new(name),
+            order: Some(V::cmp),
+            n_values: 0,
+        }
+    }
+
+    /// Identify this multi-map.
+    pub fn id(&self) -> String {
+        self.llrb.id()
+    }
+
+    /// Return number of (key, value) pairs in this multi-map.
+    pub fn len(&self) -> usize {
+        self.n_values
+    }
+
+    /// Return number of distinct keys in this multi-map.
+    pub fn len_keys(&self) -> usize {
+        self.llrb.len()
+    }
+
+    /// Check whether this multi-map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.llrb.is_empty()
+    }
+
+    /// Add `value` for `key`, along with values already present for key.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.n_values += 1;
+        let values = match self.llrb.get_mut(&key) {
+            Some(values) => values,
+            None => {
+                self.llrb.set(key, vec![value]);
+                return;
+            }
+        };
+        match self.order {
+            Some(order) => {
+                let off = values.partition_point(|v| order(v, &value) != Ordering::Greater);
+                values.insert(off, value);
+            }
+            None => values.push(value),
+        }
+    }
+
+    /// Check whether `key` is present in this multi-map.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.llrb.contains_key(key)
+    }
+
+    /// Get all values for `key`. Return an empty list if key is not
+    /// present.
+    pub fn get_all<Q>(&self, key: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.llrb.get(key).unwrap_or_default()
+    }
+
+    /// Remove the first value for `key` that is equal to `value`. Key
+    /// is removed along with its last value. Return false if there is
+    /// no such value.
+    pub fn remove_one<Q>(&mut self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: PartialEq,
+    {
+        let values = match self.llrb.get_mut(key) {
+            Some(values) => values,
+            None => return false,
+        };
+        match values.iter().position(|v| v == value) {
+            Some(off) if values.len() == 1 => {
+                debug_assert_eq!(off, 0);
+                self.llrb.delete(key);
+            }
+            Some(off) => {
+                values.remove(off);
+            }
+            None => return false,
+        }
+        self.n_values -= 1;
+        true
+    }
+
+    /// Remove `key` along with all its values. Return the values removed.
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let values = self.llrb.delete(key).unwrap_or_default();
+        self.n_values -= values.len();
+        values
+    }
+
+    /// Return an iterator over all (key, value) pairs in this multi-map,
+    /// in key order.
+    pub fn iter(&self) -> Pairs<Iter<'_, K, Vec<V>>, K, V> {
+        Pairs::new(self.llrb.iter(), false)
+    }
+
+    /// Return an iterator over (key, value) pairs, for keys from low to
+    /// high.
+    pub fn range<Q, R>(&self, range: R) -> Pairs<Range<'_, K, Vec<V>, R, Q>, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Pairs::new(self.llrb.range(range), false)
+    }
+
+    /// Return an iterator over (key, value) pairs, for keys from high to
+    /// low. Values for a key are also iterated in reverse.
+    pub fn reverse<Q, R>(&self, range: R) -> Pairs<Reverse<'_, K, Vec<V>, R, Q>, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Pairs::new(self.llrb.reverse(range), true)
+    }
+
+    /// Validate the underlying tree, refer to [`Llrb::validate`].
+    pub fn validate(&self) -> Result<Stats, Error<K>> {
+        self.llrb.validate()
+    }
+}
+
+/// Pairs flattens the values for each key into (key, value) pairs.
+pub struct Pairs<I, K, V> {
+    iter: I,
+    values: Option<(K, vec::IntoIter<V>)>,
+    rev: bool,
+}
+
+impl<I, K, V> Pairs<I, K, V> {
+    fn new(iter: I, rev: bool) -> Pairs<I, K, V> {
+        Pairs {
+            iter,
+            values: None,
+            rev,
+        }
+    }
+}
+
+impl<I, K, V> Iterator for Pairs<I, K, V>
+where
+    I: Iterator<Item = (K, Vec<V>)>,
+    K: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = self.values.as_mut() {
+                let value = match self.rev {
+                    true => values.next_back(),
+                    false => values.next(),
+                };
+                if let Some(value) = value {
+                    break Some((key.clone(), value));
+                }
+            }
+            let (key, values) = self.iter.next()?;
+            self.values = Some((key, values.into_iter()));
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "multi_test.rs"]
+mod multi_test;
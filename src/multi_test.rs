@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_multi() {
+    let mut multi: LlrbMulti<i64, u64> = LlrbMulti::new("test-multi");
+    assert_eq!(multi.id(), "test-multi".to_string());
+    assert!(multi.is_empty());
+
+    multi.insert(10, 3);
+    multi.insert(10, 1);
+    multi.insert(20, 5);
+    multi.insert(10, 3);
+    multi.insert(10, 2);
+    assert_eq!(multi.len(), 5);
+    assert_eq!(multi.len_keys(), 2);
+    assert!(multi.contains_key(&10));
+    assert_eq!(multi.get_all(&10), vec![3, 1, 3, 2]);
+    assert_eq!(multi.get_all(&30), Vec::<u64>::new());
+
+    assert!(multi.remove_one(&10, &3));
+    assert_eq!(multi.get_all(&10), vec![1, 3, 2]);
+    assert!(!multi.remove_one(&10, &4));
+    assert!(!multi.remove_one(&30, &3));
+    assert!(multi.remove_one(&20, &5));
+    assert!(!multi.contains_key(&20));
+    assert_eq!(multi.len(), 3);
+
+    multi.extend(vec![(5, 50), (15, 150), (15, 151)]);
+    let pairs: Vec<(i64, u64)> = multi.iter().collect();
+    assert_eq!(
+        pairs,
+        vec![(5, 50), (10, 1), (10, 3), (10, 2), (15, 150), (15, 151)]
+    );
+    let pairs: Vec<(i64, u64)> = multi.range(10..).collect();
+    assert_eq!(pairs, vec![(10, 1), (10, 3), (10, 2), (15, 150), (15, 151)]);
+    let pairs: Vec<(i64, u64)> = multi.reverse(..=10).collect();
+    assert_eq!(pairs, vec![(10, 2), (10, 3), (10, 1), (5, 50)]);
+
+    assert_eq!(multi.remove_all(&10), vec![1, 3, 2]);
+    assert_eq!(multi.remove_all(&10), Vec::<u64>::new());
+    assert_eq!(multi.len(), 3);
+    assert!(multi.validate().is_ok());
+}
+
+#[test]
+fn test_multi_sorted() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut multi: LlrbMulti<i64, u64> = LlrbMulti::new_sorted("test-multi");
+    let mut refs: BTreeMap<i64, Vec<u64>> = BTreeMap::new();
+    for _ in 0..10_000 {
+        let (key, value) = (rng.gen_range(0, 500), rng.gen_range(0, 10));
+        match rng.gen_range(0, 4) {
+            0 => {
+                let ok = refs.get_mut(&key).and_then(|values| {
+                    let off = values.iter().position(|v| *v == value)?;
+                    values.remove(off);
+                    Some(())
+                });
+                if refs.get(&key).is_some_and(|values| values.is_empty()) {
+                    refs.remove(&key);
+                }
+                assert_eq!(multi.remove_one(&key, &value), ok.is_some());
+            }
+            _ => {
+                multi.insert(key, value);
+                let values = refs.entry(key).or_default();
+                values.push(value);
+                values.sort();
+            }
+        }
+    }
+
+    let pairs: Vec<(i64, u64)> = refs
+        .iter()
+        .flat_map(|(k, vs)| vs.iter().map(move |v| (*k, *v)))
+        .collect();
+    assert_eq!(multi.len(), pairs.len());
+    assert_eq!(multi.len_keys(), refs.len());
+    assert!(multi.iter().eq(pairs.into_iter()));
+    assert!(multi.validate().is_ok());
+}
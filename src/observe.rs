@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use crate::aggregate::Aggregate;
+use crate::error::Error;
+use crate::llrb::Llrb;
+use crate::watch::Watches;
+
+/// Event enumerates the mutations on [`Llrb`] instance, that are
+/// reported to its observers and recorded in its change log. Refer to
+/// [`Llrb::observe`] and [`Llrb::drain_changes`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<K, V> {
+    /// Entry is inserted into the index.
+    Inserted(K, V),
+    /// Entry's value is overwritten, old value followed by new value.
+    Updated(K, V, V),
+    /// Entry is removed from the index, including entries removed by
+    /// eviction and expiry.
+    Deleted(K, V),
+    /// All entries are removed from the index, refer to [`Llrb::clear`].
+    Cleared,
+}
+
+impl<K, V> Event<K, V> {
+    /// Return the key mutated, None for [`Event::Cleared`].
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            Event::Inserted(key, _) => Some(key),
+            Event::Updated(key, _, _) => Some(key),
+            Event::Deleted(key, _) => Some(key),
+            Event::Cleared => None,
+        }
+    }
+}
+
+pub(crate) type ObserverFn<K, V> = Box<dyn FnMut(&Event<K, V>) + Send + Sync>;
+
+// Changes along with their seqno, in the order they happened.
+type Changes<K, V> = Vec<(u64, Event<K, V>)>;
+
+// Observer dispatches events to observers and watches, and records them
+// in a bounded change log. Every change is sequenced, whether or not it
+// is observed. Changes within a transaction are held back until commit.
+pub(crate) struct Observer<K, V> {
+    seqno: u64,
+    observers: Vec<ObserverFn<K, V>>,
+    pub(crate) watches: Watches<K, V>,
+    capacity: usize,
+    log: VecDeque<(u64, Event<K, V>)>,
+    held: Option<Vec<Option<Event<K, V>>>>,
+}
+
+impl<K, V> Default for Observer<K, V> {
+    fn default() -> Self {
+        Observer {
+            seqno: 0,
+            observers: vec![],
+            watches: Default::default(),
+            capacity: 0,
+            log: VecDeque::new(),
+            held: None,
+        }
+    }
+}
+
+// Closures cannot be cloned, the clone shall start without observers
+// and watches, but with the same change log. Changes held back by an
+// ongoing transaction are not cloned.
+impl<K, V> Clone for Observer<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Observer {
+            seqno: self.seqno,
+            observers: vec![],
+            watches: Default::default(),
+            capacity: self.capacity,
+            log: self.log.clone(),
+            held: None,
+        }
+    }
+}
+
+impl<K, V> Observer<K, V>
+where
+    K: Clone + Ord,
+{
+    // Whether changes shall be supplied as events.
+    #[inline]
+    pub(crate) fn is_active(&self) -> bool {
+        !self.observers.is_empty() || !self.watches.is_empty() || self.capacity > 0
+    }
+
+    // Sequence the next change, `event` is supplied only when active.
+    pub(crate) fn record(&mut self, event: Option<Event<K, V>>) {
+        if let Some(held) = self.held.as_mut() {
+            held.push(event);
+            return;
+        }
+        self.seqno += 1;
+        if let Some(event) = event {
+            self.observers
+                .iter_mut()
+                .for_each(|observer| observer(&event));
+            self.watches.dispatch(&event);
+            if self.capacity > 0 {
+                if self.log.len() == self.capacity {
+                    self.log.pop_front();
+                }
+                self.log.push_back((self.seqno, event));
+            }
+        }
+    }
+
+    // Hold back changes, until they are released.
+    pub(crate) fn hold(&mut self) {
+        self.held = Some(vec![]);
+    }
+
+    // Number of changes held back so far.
+    pub(crate) fn n_held(&self) -> usize {
+        self.held.as_ref().map_or(0, Vec::len)
+    }
+
+    // Drop changes held back after the first `n` changes.
+    pub(crate) fn truncate(&mut self, n: usize) {
+        if let Some(held) = self.held.as_mut() {
+            held.truncate(n)
+        }
+    }
+
+    // Stop holding back changes, and record the changes held so far
+    // if `publish` is true, else drop them.
+    pub(crate) fn release(&mut self, publish: bool) {
+        if let Some(held) = self.held.take() {
+            if publish {
+                held.into_iter().for_each(|event| self.record(event));
+            }
+        }
+    }
+
+    // Seqno of the oldest change held in the log.
+    fn oldest(&self) -> u64 {
+        self.log.front().map_or(self.seqno + 1, |(seqno, _)| *seqno)
+    }
+}
+
+/// Change-data-capture on Llrb instance.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Add an observer, that shall be called with an [`Event`] for every
+    /// mutation on this instance, after the index is updated. Observers
+    /// are not cloned along with the instance.
+    pub fn observe<F>(&mut self, observer: F)
+    where
+        F: 'static + FnMut(&Event<K, V>) + Send + Sync,
+    {
+        self.observer.observers.push(Box::new(observer))
+    }
+
+    /// Record the latest `capacity` changes in a change log, refer to
+    /// [`Llrb::drain_changes`]. Older changes are dropped once the log is
+    /// full. A capacity of zero, which is the default, disables the log.
+    pub fn set_change_log(&mut self, capacity: usize) {
+        let log = &mut self.observer.log;
+        log.drain(..log.len().saturating_sub(capacity));
+        self.observer.capacity = capacity;
+    }
+
+    /// Return the sequence number of the latest change on this instance.
+    /// Sequence numbers start from 1 and increase by 1 for every change.
+    pub fn seqno(&self) -> u64 {
+        self.observer.seqno
+    }
+
+    /// Drain changes, along with their sequence number, that happened
+    /// after `seqno`. Changes up to `seqno` are discarded from the log.
+    /// If some of the changes after `seqno` were dropped from the log,
+    /// return [`Error::ChangesDropped`] and leave the log untouched.
+    /// Applications can then resync with the index as of [`Llrb::seqno`].
+    pub fn drain_changes(&mut self, seqno: u64) -> Result<Changes<K, V>, Error<K>> {
+        let oldest = self.observer.oldest();
+        if seqno.saturating_add(1) < oldest {
+            return Err(Error::ChangesDropped { seqno, oldest });
+        }
+        let log = &mut self.observer.log;
+        let n = log.iter().take_while(|(s, _)| *s <= seqno).count();
+        log.drain(..n);
+        Ok(log.drain(..).collect())
+    }
+}
+
+#[cfg(test)]
+#[path = "observe_test.rs"]
+mod observe_test;
@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::evict::{Capacity, Eviction};
+use crate::test_util::make_seed;
+
+#[test]
+fn test_observe() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-observe");
+    let events = Arc::new(Mutex::new(vec![]));
+    let evs = Arc::clone(&events);
+    llrb.observe(move |event| evs.lock().unwrap().push(event.clone()));
+
+    llrb.create(10, 100).unwrap();
+    assert!(llrb.create(10, 101).is_err());
+    llrb.set(20, 200);
+    llrb.set(10, 1000);
+    llrb.set_with_ttl(30, 300, Duration::from_secs(3600));
+    assert_eq!(llrb.delete(&20), Some(200));
+    assert_eq!(llrb.delete(&20), None);
+    llrb.clear();
+    assert_eq!(llrb.len(), 0);
+    assert_eq!(llrb.get(&10), None);
+    llrb.set(10, 10);
+    assert!(llrb.validate().is_ok());
+
+    let refs = vec![
+        Event::Inserted(10, 100),
+        Event::Inserted(20, 200),
+        Event::Updated(10, 100, 1000),
+        Event::Inserted(30, 300),
+        Event::Deleted(20, 200),
+        Event::Cleared,
+        Event::Inserted(10, 10),
+    ];
+    assert_eq!(*events.lock().unwrap(), refs);
+    assert_eq!(llrb.seqno(), 7);
+
+    // clone does not carry observers.
+    let mut cloned = llrb.clone();
+    cloned.set(40, 400);
+    assert_eq!(events.lock().unwrap().len(), 7);
+    assert_eq!(cloned.seqno(), 8);
+}
+
+#[test]
+fn test_observe_mirror() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let capacity = Capacity::Entries(100);
+    let mut llrb: Llrb<i64, i64> = Llrb::new_bounded("test-mirror", capacity, Eviction::Lru);
+    let mirror = Arc::new(Mutex::new(BTreeMap::new()));
+    let m = Arc::clone(&mirror);
+    llrb.observe(move |event| {
+        let mut mirror = m.lock().unwrap();
+        match event {
+            Event::Inserted(key, value) => assert!(mirror.insert(*key, *value).is_none()),
+            Event::Updated(key, old, value) => {
+                assert_eq!(mirror.insert(*key, *value), Some(*old));
+            }
+            Event::Deleted(key, value) => assert_eq!(mirror.remove(key), Some(*value)),
+            Event::Cleared => mirror.clear(),
+        }
+    });
+
+    let ttl = Duration::from_secs(0);
+    for _ in 0..10_000 {
+        let key = rng.gen_range(0, 200);
+        match rng.gen_range(0, 10) {
+            0..=2 => {
+                let _ = llrb.create(key, key);
+            }
+            3..=5 => {
+                llrb.set(key, key + 1);
+            }
+            6 => {
+                llrb.set_with_ttl(key, key + 2, ttl);
+            }
+            7 | 8 => {
+                llrb.delete(&key);
+            }
+            _ if rng.gen_range(0, 100) == 0 => llrb.clear(),
+            _ => {
+                llrb.expire(Instant::now(), 10);
+            }
+        }
+    }
+    llrb.drain_evicted();
+    llrb.expire(Instant::now(), usize::MAX);
+
+    let entries: Vec<(i64, i64)> = llrb.iter().collect();
+    let mirror: Vec<(i64, i64)> = mirror.lock().unwrap().clone().into_iter().collect();
+    assert_eq!(entries, mirror);
+}
+
+#[test]
+fn test_change_log() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-change-log");
+    llrb.set(1, 1);
+    assert_eq!(llrb.seqno(), 1);
+    assert_eq!(llrb.drain_changes(1), Ok(vec![]));
+    let err = Error::ChangesDropped {
+        seqno: 0,
+        oldest: 2,
+    };
+    assert_eq!(llrb.drain_changes(0), Err(err));
+
+    llrb.set_change_log(4);
+    for key in 2..8 {
+        llrb.set(key, key * 10);
+    }
+    let err = Error::ChangesDropped {
+        seqno: 1,
+        oldest: 4,
+    };
+    assert_eq!(llrb.drain_changes(1), Err(err));
+
+    let refs = vec![(6, Event::Inserted(6, 60)), (7, Event::Inserted(7, 70))];
+    let mut cloned = llrb.clone();
+    assert_eq!(llrb.drain_changes(5), Ok(refs));
+    assert_eq!(llrb.drain_changes(7), Ok(vec![]));
+    assert!(llrb.drain_changes(5).is_err());
+
+    // clone carries the change log, shrinking it drops older changes.
+    cloned.set_change_log(2);
+    cloned.delete(&7);
+    let refs = vec![(7, Event::Inserted(7, 70)), (8, Event::Deleted(7, 70))];
+    assert_eq!(cloned.drain_changes(6), Ok(refs));
+}
@@ -0,0 +1,344 @@
+use std::{
+    borrow::Borrow,
+    marker, mem,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+
+use crate::llrb::{
+    build_iter, find_start, is_below, is_black, max_height, Fragment, IFlag, Llrb, Node,
+};
+use crate::ttl::Live;
+
+// Pending work in a ParIter, either a single entry or all the entries
+// in a sub-tree.
+enum Work<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    Entry(&'a Node<K, V>),
+    Tree(&'a Node<K, V>),
+}
+
+impl<'a, K, V> Work<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    // estimated number of entries, a sub-tree of black height h holds
+    // at least 2^h - 1 entries.
+    fn weight(&self) -> usize {
+        match self {
+            Work::Entry(_) => 1,
+            Work::Tree(nref) => {
+                let (mut node, mut blacks) = (Some(*nref), 0);
+                while let Some(nref) = node {
+                    blacks += is_black(node) as u32;
+                    node = nref.left_deref();
+                }
+                1_usize.checked_shl(blacks).map_or(usize::MAX, |w| w - 1)
+            }
+        }
+    }
+}
+
+/// ParIter is a splittable iterator over [`Llrb`] instance, created via
+/// [`Llrb::par_iter`] or [`Llrb::par_range`].
+///
+/// Each call to [`ParIter::split`] carves the remaining entries into two
+/// chunks along the sub-trees, such that all entries in the first chunk
+/// sort before all entries in the second. Iterating a chunk yields its
+/// entries in key order. With the `rayon` feature, ParIter implements
+/// rayon's ParallelIterator.
+pub struct ParIter<'a, K, V, R = RangeFull, Q = K>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    range: R,
+    paths: Vec<Fragment<'a, K, V>>,
+    // pending work after paths, in reverse order.
+    works: Vec<Work<'a, K, V>>,
+    live: Option<Live<'a, K>>,
+    high: marker::PhantomData<Q>,
+}
+
+/// Parallel read operations on Llrb instance.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Return a splittable iterator over all entries in this instance.
+    pub fn par_iter(&self) -> ParIter<'_, K, V> {
+        self.par_range(..)
+    }
+
+    /// Return a splittable iterator over entries from low to high.
+    pub fn par_range<Q, R>(&self, range: R) -> ParIter<'_, K, V, R, Q>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = self.root.as_deref();
+        let mut paths = Vec::with_capacity(max_height(self.n_count));
+        match range.start_bound() {
+            Bound::Unbounded => build_iter(IFlag::Left, root, &mut paths),
+            Bound::Included(low) => find_start(root, low, true, &mut paths),
+            Bound::Excluded(low) => find_start(root, low, false, &mut paths),
+        };
+        ParIter {
+            range,
+            paths,
+            works: vec![],
+            live: self.live(),
+            high: marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, R, Q> ParIter<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    R: RangeBounds<Q> + Clone,
+    Q: Ord + ?Sized,
+{
+    /// Split the remaining entries into two chunks, entries in the first
+    /// chunk sort before entries in the second chunk. Return None for
+    /// the second chunk if there is nothing left to split.
+    pub fn split(mut self) -> (Self, Option<Self>) {
+        // convert the fragment stack into pending work, pending work
+        // that is already there sorts after the fragment stack.
+        let mut works = mem::take(&mut self.works);
+        for path in self.paths.drain(..) {
+            match path.flag {
+                IFlag::Left => {
+                    if let Some(right) = path.nref.right_deref() {
+                        works.push(Work::Tree(right));
+                    }
+                    works.push(Work::Entry(path.nref));
+                }
+                IFlag::Center => {
+                    if let Some(right) = path.nref.right_deref() {
+                        works.push(Work::Tree(right));
+                    }
+                }
+                IFlag::Right => (),
+            }
+        }
+        clip(&mut works, &self.range);
+        // split sub-trees around their root, until none of them holds
+        // more than an eighth of the entries, so that they can be shared
+        // evenly between the chunks.
+        loop {
+            let total: usize = works.iter().map(Work::weight).sum();
+            let dominant = works
+                .iter()
+                .position(|work| matches!(work, Work::Tree(_)) && work.weight() * 8 > total);
+            let (off, nref) = match dominant.map(|off| (off, &works[off])) {
+                Some((off, Work::Tree(nref))) => (off, *nref),
+                _ => break,
+            };
+            let mut split = vec![];
+            if let Some(right) = nref.right_deref() {
+                split.push(Work::Tree(right));
+            }
+            split.push(Work::Entry(nref));
+            if let Some(left) = nref.left_deref() {
+                split.push(Work::Tree(left));
+            }
+            works.splice(off..(off + 1), split);
+        }
+        self.works = works;
+
+        // pending work is in reverse key order, hand over the works
+        // at the bottom of the stack, weighing about half the entries,
+        // to the second chunk. Neither chunk is left with a lone entry.
+        let n = self.works.len();
+        let total: usize = self.works.iter().map(Work::weight).sum();
+        let (mut off, mut weight) = (0, 0);
+        while off < n && (weight + self.works[off].weight()) * 2 <= total {
+            weight += self.works[off].weight();
+            off += 1;
+        }
+        let lone = |works: &[Work<K, V>]| works.iter().map(Work::weight).sum::<usize>() < 2;
+        off = off.max(1);
+        if off < n && lone(&self.works[..off]) {
+            off += 1;
+        }
+        if off >= n || lone(&self.works[..off]) || lone(&self.works[off..]) {
+            return (self, None);
+        }
+        let works = self.works.drain(..off).collect();
+        let other = ParIter {
+            range: self.range.clone(),
+            paths: Vec::with_capacity(self.paths.capacity()),
+            works,
+            live: self.live,
+            high: marker::PhantomData,
+        };
+        (self, Some(other))
+    }
+}
+
+// Drop pending work above the high bound of range, splitting the
+// sub-tree that straddles it, so that only entries in range are weighed.
+fn clip<K, V, R, Q>(works: &mut Vec<Work<'_, K, V>>, range: &R)
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    loop {
+        let (nref, tree) = match works.first() {
+            Some(Work::Entry(nref)) => (*nref, false),
+            Some(Work::Tree(nref)) => (*nref, true),
+            None => break,
+        };
+        if !is_below(range, &nref.key) {
+            // entry, and its right sub-tree, sort above the range.
+            match nref.left_deref() {
+                Some(left) if tree => works[0] = Work::Tree(left),
+                _ => {
+                    works.remove(0);
+                }
+            }
+            continue;
+        } else if !tree {
+            break;
+        }
+        let mut max = nref;
+        while let Some(right) = max.right_deref() {
+            max = right;
+        }
+        if is_below(range, &max.key) {
+            break;
+        }
+        let mut split = vec![];
+        split.extend(nref.right_deref().map(Work::Tree));
+        split.push(Work::Entry(nref));
+        split.extend(nref.left_deref().map(Work::Tree));
+        works.splice(0..1, split);
+    }
+}
+
+impl<'a, K, V, R, Q> ParIter<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    fn next_entry(&mut self) -> Option<(K, V)> {
+        loop {
+            let path = match self.paths.last_mut() {
+                Some(path) => path,
+                None => match self.works.pop()? {
+                    Work::Entry(nref) => {
+                        break Some((nref.key.clone(), nref.value.clone()));
+                    }
+                    Work::Tree(nref) => {
+                        build_iter(IFlag::Left, Some(nref), &mut self.paths);
+                        continue;
+                    }
+                },
+            };
+            match (path.flag, path.nref) {
+                (IFlag::Left, nref) => {
+                    path.flag = IFlag::Center;
+                    break Some((nref.key.clone(), nref.value.clone()));
+                }
+                (IFlag::Center, nref) => {
+                    path.flag = IFlag::Right;
+                    let rnref = nref.right_deref();
+                    build_iter(IFlag::Left, rnref, &mut self.paths);
+                }
+                (_, _) => {
+                    self.paths.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, V, R, Q> Iterator for ParIter<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q>,
+    V: Clone,
+    R: RangeBounds<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (k, v) = self.next_entry()?;
+            let ok = match self.range.end_bound() {
+                Bound::Included(high) => k.borrow().le(high),
+                Bound::Excluded(high) => k.borrow().lt(high),
+                Bound::Unbounded => true,
+            };
+            match &self.live {
+                _ if !ok => {
+                    self.paths.clear();
+                    self.works.clear();
+                    break None;
+                }
+                Some(live) if !live.is_live(&k) => continue,
+                _ => break Some((k, v)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, K, V, R, Q> UnindexedProducer for ParIter<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q> + Send + Sync,
+    V: Clone + Send + Sync,
+    R: RangeBounds<Q> + Clone + Send,
+    Q: Ord + ?Sized + Send + Sync,
+{
+    type Item = (K, V);
+
+    fn split(self) -> (Self, Option<Self>) {
+        ParIter::split(self)
+    }
+
+    fn fold_with<F>(self, folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        folder.consume_iter(self)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, K, V, R, Q> rayon::iter::ParallelIterator for ParIter<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q> + Send + Sync,
+    V: Clone + Send + Sync,
+    R: RangeBounds<Q> + Clone + Send,
+    Q: Ord + ?Sized + Send + Sync,
+{
+    type Item = (K, V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge_unindexed(self, consumer)
+    }
+}
+
+#[cfg(test)]
+#[path = "par_test.rs"]
+mod par_test;
@@ -0,0 +1,162 @@
+use std::ops::Bound;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+// split the iterator recursively, until no more splits or `depth` is
+// reached, and collect the chunks in key order.
+fn split_all<R>(iter: ParIter<i64, i64, R, i64>, depth: usize, chunks: &mut Vec<Vec<(i64, i64)>>)
+where
+    R: RangeBounds<i64> + Clone,
+{
+    if depth == 0 {
+        chunks.push(iter.collect());
+        return;
+    }
+    match iter.split() {
+        (iter, None) => chunks.push(iter.collect()),
+        (first, Some(second)) => {
+            split_all(first, depth - 1, chunks);
+            split_all(second, depth - 1, chunks);
+        }
+    }
+}
+
+#[test]
+fn test_par_iter() {
+    let llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    assert_eq!(llrb.par_iter().count(), 0);
+    assert!(llrb.par_iter().split().1.is_none());
+
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for _ in 0..10_000 {
+        let key = rng.gen_range(0, 100_000);
+        llrb.set(key, key * 10);
+    }
+    let refs: Vec<(i64, i64)> = llrb.iter().collect();
+
+    assert_eq!(llrb.par_iter().collect::<Vec<(i64, i64)>>(), refs);
+    for depth in 1..12 {
+        let mut chunks = vec![];
+        split_all(llrb.par_iter(), depth, &mut chunks);
+        assert!(chunks.len() > 1);
+        let entries: Vec<(i64, i64)> = chunks.into_iter().flatten().collect();
+        assert_eq!(entries, refs);
+    }
+
+    // split after partial iteration.
+    let mut iter = llrb.par_iter();
+    let head: Vec<(i64, i64)> = iter.by_ref().take(100).collect();
+    let (first, second) = iter.split();
+    let mut entries = head;
+    entries.extend(first);
+    entries.extend(second.unwrap());
+    assert_eq!(entries, refs);
+}
+
+#[test]
+fn test_par_split_balance() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for _ in 0..100_000 {
+        let key = rng.gen_range(0, 1_000_000);
+        llrb.set(key, key);
+    }
+    let n = llrb.len();
+
+    // split the iterator recursively, into chunks of about the same size.
+    let mut iters = vec![llrb.par_iter()];
+    for _ in 0..4 {
+        let mut splits = vec![];
+        for iter in iters.into_iter() {
+            let (first, second) = iter.split();
+            splits.push(first);
+            splits.push(second.unwrap());
+        }
+        iters = splits;
+    }
+    let sizes: Vec<usize> = iters.into_iter().map(|iter| iter.count()).collect();
+    println!("chunk sizes {:?}", sizes);
+    assert_eq!(sizes.iter().sum::<usize>(), n);
+    let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+    assert!(*max < min * 5, "{:?}", sizes);
+
+    // bounded ranges are split along the entries within range.
+    for (low, high) in [(0, 100_000), (400_000, 600_000), (900_000, 1_000_000)].iter() {
+        let mut chunks = vec![];
+        split_all(llrb.par_range(*low..*high), 3, &mut chunks);
+        let sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+        println!("range {}..{} chunk sizes {:?}", low, high, sizes);
+        assert_eq!(sizes.len(), 8);
+        assert_eq!(sizes.iter().sum::<usize>(), llrb.range(*low..*high).count());
+        let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+        assert!(*max < min * 5, "{:?}", sizes);
+    }
+
+    // small iterators are not split into lone entries.
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for key in 0..3 {
+        llrb.set(key, key);
+        let mut chunks = vec![];
+        split_all(llrb.par_iter(), 4, &mut chunks);
+        assert_eq!(chunks.len(), 1);
+    }
+}
+
+#[test]
+fn test_par_range() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    for _ in 0..10_000 {
+        let key = rng.gen_range(0, 100_000);
+        llrb.set(key, key * 10);
+    }
+
+    for _ in 0..100 {
+        let low = rng.gen_range(0, 100_000);
+        let high = rng.gen_range(low, 100_000);
+        let range = (Bound::Excluded(low), Bound::Included(high));
+        let refs: Vec<(i64, i64)> = llrb.range(range).collect();
+
+        let mut chunks = vec![];
+        split_all(llrb.par_range(range), 8, &mut chunks);
+        let entries: Vec<(i64, i64)> = chunks.into_iter().flatten().collect();
+        assert_eq!(entries, refs);
+
+        let mut chunks = vec![];
+        split_all(llrb.par_range(low..high), 8, &mut chunks);
+        let entries: Vec<(i64, i64)> = chunks.into_iter().flatten().collect();
+        let refs: Vec<(i64, i64)> = llrb.range(low..high).collect();
+        assert_eq!(entries, refs);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_rayon() {
+    use rayon::iter::ParallelIterator;
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..100_000).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let sum: i64 = ParallelIterator::map(llrb.par_iter(), |(_, v)| v).sum();
+    assert_eq!(sum, (0..100_000).sum::<i64>());
+
+    let entries: Vec<(i64, i64)> = ParallelIterator::collect(llrb.par_range(1000..2000));
+    let refs: Vec<(i64, i64)> = llrb.range(1000..2000).collect();
+    let mut entries = entries;
+    entries.sort();
+    assert_eq!(entries, refs);
+}
@@ -0,0 +1,117 @@
+use std::{
+    borrow::Borrow,
+    char,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::llrb::{Llrb, Range, Reverse};
+
+/// Prefix scan operations, for byte-string and string keys. Prefix is
+/// scanned as a range, from the prefix itself up to the smallest key that
+/// sorts after all keys starting with prefix.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Return an iterator over all entries whose key starts with
+    /// `prefix`, in sort order.
+    pub fn prefix(&self, prefix: &[u8]) -> Range<'_, K, V, PrefixRange<[u8]>, [u8]>
+    where
+        K: Borrow<[u8]>,
+    {
+        self.range(PrefixRange::new(prefix, bytes_successor(prefix)))
+    }
+
+    /// Same as [`Llrb::prefix`], in reverse sort order.
+    pub fn prefix_reverse(&self, prefix: &[u8]) -> Reverse<'_, K, V, PrefixRange<[u8]>, [u8]>
+    where
+        K: Borrow<[u8]>,
+    {
+        self.reverse(PrefixRange::new(prefix, bytes_successor(prefix)))
+    }
+
+    /// Return an iterator over all entries whose key starts with
+    /// `prefix`, in sort order.
+    pub fn prefix_str(&self, prefix: &str) -> Range<'_, K, V, PrefixRange<str>, str>
+    where
+        K: Borrow<str>,
+    {
+        self.range(PrefixRange::new(prefix, str_successor(prefix)))
+    }
+
+    /// Same as [`Llrb::prefix_str`], in reverse sort order.
+    pub fn prefix_str_reverse(&self, prefix: &str) -> Reverse<'_, K, V, PrefixRange<str>, str>
+    where
+        K: Borrow<str>,
+    {
+        self.reverse(PrefixRange::new(prefix, str_successor(prefix)))
+    }
+}
+
+/// Range of keys starting with a prefix, refer to [`Llrb::prefix`].
+pub struct PrefixRange<Q>
+where
+    Q: ToOwned + ?Sized,
+{
+    low: Q::Owned,
+    high: Option<Q::Owned>,
+}
+
+impl<Q> PrefixRange<Q>
+where
+    Q: ToOwned + ?Sized,
+{
+    fn new(prefix: &Q, high: Option<Q::Owned>) -> PrefixRange<Q> {
+        let low = prefix.to_owned();
+        PrefixRange { low, high }
+    }
+}
+
+impl<Q> RangeBounds<Q> for PrefixRange<Q>
+where
+    Q: ToOwned + ?Sized,
+{
+    fn start_bound(&self) -> Bound<&Q> {
+        Bound::Included(self.low.borrow())
+    }
+
+    fn end_bound(&self) -> Bound<&Q> {
+        match &self.high {
+            Some(high) => Bound::Excluded(high.borrow()),
+            None => Bound::Unbounded,
+        }
+    }
+}
+
+// Return the smallest byte-string that sorts after all byte-strings
+// starting with `prefix`. None if there is no such byte-string, that is,
+// prefix is empty or all 0xFF.
+fn bytes_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let n = prefix.iter().rposition(|b| *b != 0xFF)?;
+    let mut high = prefix[..=n].to_vec();
+    high[n] += 1;
+    Some(high)
+}
+
+// Return the smallest string that sorts after all strings starting with
+// `prefix`. None if there is no such string, that is, prefix is empty
+// or all char::MAX.
+fn str_successor(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(ch) = chars.pop() {
+        let next = match ch as u32 + 1 {
+            0xD800 => Some('\u{E000}'), // skip surrogates.
+            code => char::from_u32(code),
+        };
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[path = "prefix_test.rs"]
+mod prefix_test;
@@ -0,0 +1,106 @@
+use super::*;
+
+#[test]
+fn test_successor() {
+    assert_eq!(bytes_successor(b""), None);
+    assert_eq!(bytes_successor(b"\xff\xff"), None);
+    assert_eq!(bytes_successor(b"ab"), Some(b"ac".to_vec()));
+    assert_eq!(bytes_successor(b"a\xff"), Some(b"b".to_vec()));
+    assert_eq!(bytes_successor(b"a\xfe\xff\xff"), Some(b"a\xff".to_vec()));
+
+    assert_eq!(str_successor(""), None);
+    assert_eq!(str_successor("\u{10FFFF}"), None);
+    assert_eq!(str_successor("ab"), Some("ac".to_string()));
+    assert_eq!(str_successor("a\u{10FFFF}"), Some("b".to_string()));
+    assert_eq!(str_successor("a\u{D7FF}"), Some("a\u{E000}".to_string()));
+    assert_eq!(str_successor("a\u{FF}"), Some("a\u{100}".to_string()));
+}
+
+#[test]
+fn test_prefix_bytes() {
+    let keys: Vec<Vec<u8>> = vec![
+        b"".to_vec(),
+        b"a".to_vec(),
+        b"a\x00".to_vec(),
+        b"a\xfe".to_vec(),
+        b"a\xff".to_vec(),
+        b"a\xff\x00".to_vec(),
+        b"a\xff\xff".to_vec(),
+        b"b".to_vec(),
+        b"\xff".to_vec(),
+        b"\xff\xff".to_vec(),
+        b"\xff\xff\x01".to_vec(),
+    ];
+    let mut llrb: Llrb<Vec<u8>, usize> = Llrb::new("test-llrb");
+    keys.iter().enumerate().for_each(|(i, k)| {
+        llrb.set(k.clone(), i);
+    });
+
+    let prefixes: Vec<&[u8]> = vec![
+        b"",
+        b"a",
+        b"a\xff",
+        b"a\xff\xff",
+        b"b",
+        b"c",
+        b"\xff",
+        b"\xff\xff",
+    ];
+    for prefix in prefixes {
+        let refs: Vec<Vec<u8>> = keys
+            .iter()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect();
+        let items: Vec<Vec<u8>> = llrb.prefix(prefix).map(|(k, _)| k).collect();
+        assert_eq!(items, refs, "prefix {:?}", prefix);
+
+        let refs: Vec<Vec<u8>> = refs.into_iter().rev().collect();
+        let items: Vec<Vec<u8>> = llrb.prefix_reverse(prefix).map(|(k, _)| k).collect();
+        assert_eq!(items, refs, "prefix {:?}", prefix);
+    }
+}
+
+#[test]
+fn test_prefix_str() {
+    let keys = vec![
+        "",
+        "ab",
+        "abc",
+        "ab\u{D7FF}",
+        "ab\u{E000}",
+        "ab\u{10FFFF}",
+        "ab\u{10FFFF}z",
+        "ac",
+        "\u{10FFFF}",
+        "\u{10FFFF}\u{10FFFF}",
+    ];
+    let mut llrb: Llrb<String, usize> = Llrb::new("test-llrb");
+    keys.iter().enumerate().for_each(|(i, k)| {
+        llrb.set(k.to_string(), i);
+    });
+
+    let prefixes = vec![
+        "",
+        "a",
+        "ab",
+        "ab\u{D7FF}",
+        "ab\u{10FFFF}",
+        "ac",
+        "b",
+        "\u{10FFFF}",
+    ];
+    for prefix in prefixes {
+        let refs: Vec<String> = keys
+            .iter()
+            .filter(|k| k.starts_with(prefix))
+            .map(|k| k.to_string())
+            .collect();
+        let items: Vec<String> = llrb.prefix_str(prefix).map(|(k, _)| k).collect();
+        assert_eq!(items, refs, "prefix {:?}", prefix);
+
+        let refs: Vec<String> = refs.into_iter().rev().collect();
+        let items: Vec<String> = llrb.prefix_str_reverse(prefix).map(|(k, _)| k).collect();
+        assert_eq!(items, refs, "prefix {:?}", prefix);
+    }
+}
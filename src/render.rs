@@ -0,0 +1,216 @@
+use std::{borrow::Borrow, fmt::Debug, ops::RangeBounds};
+
+use crate::aggregate::Aggregate;
+use crate::llrb::{is_above, is_below, Llrb, Node};
+
+// Sub-tree as rendered, limited to a range of keys and a maximum depth.
+// Nodes out of range are skipped, linking their parent to the first node
+// in range down the tree.
+struct View<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    nref: Option<&'a Node<K, V, A>>, // None, for sub-tree beyond depth.
+    depth: usize,
+    skipped: bool, // link from parent skips nodes out of range.
+    left: Option<Box<View<'a, K, V, A>>>,
+    right: Option<Box<View<'a, K, V, A>>>,
+}
+
+impl<'a, K, V, A> View<'a, K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    fn new<Q, R>(
+        node: Option<&'a Node<K, V, A>>,
+        range: &R,
+        depth: usize,
+        max: usize,
+    ) -> Option<Box<Self>>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (mut node, mut depth, mut skipped) = (node, depth, false);
+        let (nref, left, right) = loop {
+            let nref = node?;
+            if depth > max {
+                break (None, None, None);
+            } else if !is_above(range, &nref.key) {
+                node = nref.right_deref();
+            } else if !is_below(range, &nref.key) {
+                node = nref.left_deref();
+            } else {
+                let left = View::new(nref.left_deref(), range, depth + 1, max);
+                let right = View::new(nref.right_deref(), range, depth + 1, max);
+                break (Some(nref), left, right);
+            }
+            depth += 1;
+            skipped = true;
+        };
+        Some(Box::new(View {
+            nref,
+            depth,
+            skipped,
+            left,
+            right,
+        }))
+    }
+
+    fn is_red(&self) -> bool {
+        self.nref.is_some_and(|nref| !nref.black)
+    }
+
+    // Render sub-tree in dot format, return the id of its root.
+    fn to_dot(&self, next: &mut usize, out: &mut String) -> usize
+    where
+        K: Debug,
+    {
+        let id = *next;
+        *next += 1;
+        match self.nref {
+            Some(nref) => {
+                let label = quote(&format!("{:?}", nref.key));
+                let colour = if nref.black { "black" } else { "red" };
+                out.push_str(&format!(
+                    "    n{} [label={}, fillcolor={}];\n",
+                    id, label, colour
+                ));
+            }
+            None => {
+                let attrs = "label=\"...\", shape=plaintext, fontcolor=black";
+                out.push_str(&format!("    n{} [{}];\n", id, attrs));
+            }
+        }
+        for child in [&self.left, &self.right].iter().filter_map(|c| c.as_ref()) {
+            let child_id = child.to_dot(next, out);
+            let attrs = match (child.skipped, child.is_red()) {
+                (true, _) => " [style=dashed]",
+                (false, true) => " [color=red, penwidth=2]",
+                (false, false) => "",
+            };
+            out.push_str(&format!("    n{} -> n{}{};\n", id, child_id, attrs));
+        }
+        id
+    }
+
+    // Render sub-tree sideways, right sub-tree above and left sub-tree
+    // below, `link` leads from the parent.
+    fn to_pretty(&self, link: char, out: &mut String)
+    where
+        K: Debug,
+    {
+        if let Some(right) = &self.right {
+            right.to_pretty('/', out);
+        }
+        let indent = "    ".repeat(self.depth - 1);
+        let link = match (link, self.skipped) {
+            (' ', false) => "".to_string(),
+            (' ', true) => ".. ".to_string(),
+            (link, false) => format!("{}-- ", link),
+            (link, true) => format!("{}.. ", link),
+        };
+        let label = match self.nref {
+            Some(nref) => {
+                let colour = if nref.black { 'B' } else { 'R' };
+                format!("{:?} ({}, {})", nref.key, colour, self.depth)
+            }
+            None => "...".to_string(),
+        };
+        out.push_str(&format!("{}{}{}\n", indent, link, label));
+        if let Some(left) = &self.left {
+            left.to_pretty('\\', out);
+        }
+    }
+}
+
+/// Render operations on Llrb instance, to teach and debug.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord + Debug,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Render the tree in graphviz dot format. Nodes are labelled with
+    /// their key, and filled with their colour, red links are drawn red.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_range::<K, _>(.., usize::MAX)
+    }
+
+    /// Same as [`Llrb::to_dot`], limited to nodes from low to high, and
+    /// up to `depth`, root being at depth 1. Links that skip over nodes
+    /// out of range are dashed, sub-trees beyond `depth` are elided.
+    pub fn to_dot_range<Q, R>(&self, range: R, depth: usize) -> String
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut out = format!("digraph {} {{\n", quote(&self.id()));
+        out.push_str("    node [shape=circle, style=filled, fontcolor=white];\n");
+        if let Some(view) = View::new(self.root.as_deref(), &range, 1, depth) {
+            view.to_dot(&mut 0, &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the tree sideways, one node per line, right sub-tree above
+    /// its parent and left sub-tree below. Nodes are labelled with their
+    /// key, colour and depth, root being at depth 1.
+    ///
+    /// ```
+    /// use llrb_index::Llrb;
+    /// let mut llrb: Llrb<i32, i32> = Llrb::new("myinstance");
+    /// (1..=3).for_each(|key| { llrb.set(key, key); });
+    /// let tree = concat!(
+    ///     "    /-- 3 (B, 2)\n",
+    ///     "2 (B, 1)\n",
+    ///     "    \\-- 1 (B, 2)\n",
+    /// );
+    /// assert_eq!(llrb.pretty_tree(), tree);
+    /// ```
+    pub fn pretty_tree(&self) -> String {
+        self.pretty_tree_range::<K, _>(.., usize::MAX)
+    }
+
+    /// Same as [`Llrb::pretty_tree`], limited to nodes from low to high,
+    /// and up to `depth`. Links that skip over nodes out of range are
+    /// dotted, sub-trees beyond `depth` are elided.
+    pub fn pretty_tree_range<Q, R>(&self, range: R, depth: usize) -> String
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut out = String::new();
+        if let Some(view) = View::new(self.root.as_deref(), &range, 1, depth) {
+            view.to_pretty(' ', &mut out);
+        }
+        out
+    }
+}
+
+// Quote and escape a string as dot identifier.
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+#[path = "render_test.rs"]
+mod render_test;
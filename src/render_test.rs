@@ -0,0 +1,146 @@
+use std::ops::Bound;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_pretty_tree() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-pretty-tree");
+    assert_eq!(llrb.pretty_tree(), "");
+    (1..=10).for_each(|key| {
+        llrb.set(key, key);
+    });
+
+    let tree = concat!(
+        "        /-- 10 (B, 3)\n",
+        "            \\-- 9 (R, 4)\n",
+        "    /-- 8 (B, 2)\n",
+        "            /-- 7 (B, 4)\n",
+        "        \\-- 6 (R, 3)\n",
+        "            \\-- 5 (B, 4)\n",
+        "4 (B, 1)\n",
+        "        /-- 3 (B, 3)\n",
+        "    \\-- 2 (B, 2)\n",
+        "        \\-- 1 (B, 3)\n",
+    );
+    assert_eq!(llrb.pretty_tree(), tree);
+
+    let tree = concat!(
+        "            /-- ...\n",
+        "        /.. 6 (R, 3)\n",
+        "            \\-- ...\n",
+        "4 (B, 1)\n",
+    );
+    assert_eq!(llrb.pretty_tree_range(4..=7, 3), tree);
+
+    let tree = concat!(
+        "            /-- 7 (B, 4)\n",
+        "        .. 6 (R, 3)\n",
+        "            \\-- 5 (B, 4)\n",
+    );
+    assert_eq!(llrb.pretty_tree_range(5..=7, 10), tree);
+    assert_eq!(llrb.pretty_tree_range(11.., 10), "");
+}
+
+#[test]
+fn test_to_dot() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-to-dot");
+    let dot = concat!(
+        "digraph \"test-to-dot\" {\n",
+        "    node [shape=circle, style=filled, fontcolor=white];\n",
+        "}\n",
+    );
+    assert_eq!(llrb.to_dot(), dot);
+    (1..=10).for_each(|key| {
+        llrb.set(key, key);
+    });
+
+    let dot = concat!(
+        "digraph \"test-to-dot\" {\n",
+        "    node [shape=circle, style=filled, fontcolor=white];\n",
+        "    n0 [label=\"4\", fillcolor=black];\n",
+        "    n1 [label=\"6\", fillcolor=red];\n",
+        "    n2 [label=\"...\", shape=plaintext, fontcolor=black];\n",
+        "    n1 -> n2;\n",
+        "    n3 [label=\"...\", shape=plaintext, fontcolor=black];\n",
+        "    n1 -> n3;\n",
+        "    n0 -> n1 [style=dashed];\n",
+        "}\n",
+    );
+    assert_eq!(llrb.to_dot_range(4..=7, 3), dot);
+
+    // every node is linked to its parent, red links drawn red.
+    let dot = llrb.to_dot();
+    assert_eq!(dot.matches("fillcolor").count(), 10);
+    assert_eq!(dot.matches(" -> ").count(), 9);
+    assert_eq!(dot.matches("fillcolor=red").count(), 2);
+    assert_eq!(dot.matches("color=red, penwidth=2").count(), 2);
+    assert_eq!(dot.matches("dashed").count(), 0);
+
+    let mut llrb: Llrb<String, i64> = Llrb::new("test \"dot\"");
+    llrb.set("say \"hi\"\\".to_string(), 1);
+    let dot = llrb.to_dot();
+    assert!(
+        dot.starts_with("digraph \"test \\\"dot\\\"\" {\n"),
+        "{}",
+        dot
+    );
+    assert!(dot.contains("[label=\"\\\"say \\\\\\\"hi\\\\\\\"\\\\\\\\\\\"\","));
+}
+
+#[test]
+fn test_pretty_tree_random() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-pretty-tree-random");
+    for _ in 0..1000 {
+        let key = rng.gen_range(0, 1000);
+        llrb.set(key, key);
+    }
+
+    for _ in 0..100 {
+        let (a, b) = (rng.gen_range(-10, 1010), rng.gen_range(-10, 1010));
+        let low = match rng.gen_range(0, 3) {
+            0 => Bound::Included(a),
+            1 => Bound::Excluded(a),
+            _ => Bound::Unbounded,
+        };
+        let high = match rng.gen_range(0, 3) {
+            0 => Bound::Included(b),
+            1 => Bound::Excluded(b),
+            _ => Bound::Unbounded,
+        };
+        let depth = rng.gen_range(1, 20);
+
+        // nodes are rendered in reverse sort order.
+        let mut keys = vec![];
+        for line in llrb.pretty_tree_range((low, high), depth).lines() {
+            let line = line
+                .trim_start()
+                .trim_start_matches(|c| "/\\-. ".contains(c));
+            if line.is_empty() {
+                continue; // sub-tree beyond depth.
+            }
+            let mut fields = line.split(|c| " (,)".contains(c)).filter(|s| !s.is_empty());
+            let key: i64 = fields.next().unwrap().parse().unwrap();
+            let black = fields.next().unwrap() == "B";
+            let d: usize = fields.next().unwrap().parse().unwrap();
+            let explain = llrb.explain_get(&key);
+            let visit = explain.visits().last().unwrap();
+            assert_eq!((visit.is_black(), visit.depth()), (black, d));
+            keys.push(key);
+        }
+        keys.reverse();
+
+        let refs: Vec<i64> = llrb
+            .range((low, high))
+            .map(|(key, _)| key)
+            .filter(|key| llrb.explain_get(key).depth().unwrap() <= depth)
+            .collect();
+        assert_eq!(keys, refs);
+    }
+}
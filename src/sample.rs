@@ -0,0 +1,292 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::BTreeSet,
+    ops::{Bound, RangeBounds},
+};
+
+use rand::Rng;
+
+use crate::aggregate::Aggregate;
+use crate::llrb::{build_iter, find_start, is_above, is_below, max_height, IFlag, Llrb, Node};
+use crate::ttl::Live;
+
+// Entries picked by rank might have expired, pick as many times before
+// falling back to a scan.
+const RETRIES: usize = 16;
+
+/// Sampling operations on Llrb instance.
+///
+/// Instances that maintain a counting aggregate, like `Llrb<K, V, Count>`
+/// with [`Count`](crate::Count), are sampled in O(log n) per entry, by
+/// locating entries with their rank. Other instances are sampled by
+/// scanning the entries, costing O(n) for every sample, use
+/// `Llrb<K, V, Count>` when sampling is frequent.
+///
+/// Entries whose time-to-live has lapsed, but not yet removed, are never
+/// sampled, same as [`Llrb::get`] and [`Llrb::iter`]. Ranks landing on
+/// such entries are picked again, and after repeated misses sampling
+/// falls back to a scan.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Return an entry picked uniformly at random, None if this instance
+    /// is empty.
+    pub fn sample_uniform<G: Rng>(&self, rng: &mut G) -> Option<(K, V)> {
+        self.sample_range::<K, _, _>(.., rng)
+    }
+
+    /// Return an entry, from low to high, picked uniformly at random.
+    /// None if there is no entry in the range.
+    pub fn sample_range<Q, R, G>(&self, range: R, rng: &mut G) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+        G: Rng,
+    {
+        let live = self.live();
+        let nref = if self.is_counted() {
+            let low = self.position(|key| !is_above(&range, key));
+            let high = self.position(|key| is_below(&range, key));
+            if high <= low {
+                return None;
+            }
+            let item = (0..RETRIES)
+                .filter_map(|_| self.select(rng.gen_range(low, high)))
+                .find(|nref| is_live(live, nref));
+            item.or_else(|| self.reservoir(&range, live, rng))
+        } else {
+            self.reservoir(&range, live, rng)
+        }?;
+        Some((nref.key.clone(), nref.value.clone()))
+    }
+
+    /// Return `k` distinct entries, picked uniformly at random, in sort
+    /// order. If this instance holds `k` entries or less, return all of
+    /// them.
+    pub fn sample_k<G: Rng>(&self, k: usize, rng: &mut G) -> Vec<(K, V)> {
+        let live = self.live();
+        let nodes = match self.is_counted() {
+            true if live.is_none() => {
+                // Floyd's algorithm, pick k distinct ranks from 0..n.
+                let n = self.len();
+                let mut ranks = BTreeSet::new();
+                for j in n.saturating_sub(k)..n {
+                    let rank = rng.gen_range(0, j + 1);
+                    if !ranks.insert(rank) {
+                        ranks.insert(j);
+                    }
+                }
+                ranks.into_iter().filter_map(|r| self.select(r)).collect()
+            }
+            true => match self.pick_k(k, live, rng) {
+                Some(nodes) => nodes,
+                None => self.reservoir_k(k, live, rng),
+            },
+            false => self.reservoir_k(k, live, rng),
+        };
+        let iter = nodes.into_iter();
+        iter.map(|nref| (nref.key.clone(), nref.value.clone()))
+            .collect()
+    }
+
+    /// Return an entry picked at random, with probability proportional
+    /// to its `weight`. Entries whose weight is not a positive number are
+    /// never picked. Return None if no entry can be picked. Weights are
+    /// computed for every entry, costing a full scan.
+    pub fn sample_weighted<F, G>(&self, weight: F, rng: &mut G) -> Option<(K, V)>
+    where
+        F: Fn(&K, &V) -> f64,
+        G: Rng,
+    {
+        let live = self.live();
+        // weighted reservoir of one entry.
+        let (mut total, mut item) = (0.0, None);
+        self.walk::<K, _, _>(&(..), |nref| {
+            if !is_live(live, nref) {
+                return;
+            }
+            let w = weight(&nref.key, &nref.value);
+            if w > 0.0 && w.is_finite() {
+                total += w;
+                if rng.gen::<f64>() * total < w {
+                    item = Some(nref)
+                }
+            }
+        });
+        item.map(|nref| (nref.key.clone(), nref.value.clone()))
+    }
+
+    // Reservoir of one live entry, from low to high.
+    fn reservoir<Q, R, G>(
+        &self,
+        range: &R,
+        live: Option<Live<K>>,
+        rng: &mut G,
+    ) -> Option<&Node<K, V, A>>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+        G: Rng,
+    {
+        let (mut n, mut item) = (0, None);
+        self.walk(range, |nref| {
+            if is_live(live, nref) {
+                n += 1;
+                if rng.gen_range(0, n) == 0 {
+                    item = Some(nref)
+                }
+            }
+        });
+        item
+    }
+
+    // Reservoir of k live entries, in sort order.
+    fn reservoir_k<G>(&self, k: usize, live: Option<Live<K>>, rng: &mut G) -> Vec<&Node<K, V, A>>
+    where
+        G: Rng,
+    {
+        // along with their rank.
+        let mut items = Vec::with_capacity(k.min(self.len()));
+        let mut n = 0;
+        self.walk::<K, _, _>(&(..), |nref| {
+            if !is_live(live, nref) {
+                return;
+            }
+            if items.len() < k {
+                items.push((n, nref));
+            } else {
+                let j = rng.gen_range(0, n + 1);
+                if j < k {
+                    items[j] = (n, nref);
+                }
+            }
+            n += 1;
+        });
+        items.sort_by_key(|(rank, _)| *rank);
+        items.into_iter().map(|(_, nref)| nref).collect()
+    }
+
+    // Pick distinct ranks at random, skipping expired entries, until `k`
+    // live entries are picked. None if that takes too many picks.
+    fn pick_k<G>(&self, k: usize, live: Option<Live<K>>, rng: &mut G) -> Option<Vec<&Node<K, V, A>>>
+    where
+        G: Rng,
+    {
+        let n = self.len();
+        let (mut tried, mut ranks) = (BTreeSet::new(), BTreeSet::new());
+        for _ in 0..(k * 2 + RETRIES) {
+            if ranks.len() == k || tried.len() == n {
+                break;
+            }
+            let rank = rng.gen_range(0, n);
+            if tried.insert(rank) && matches!(self.select(rank), Some(nref) if is_live(live, nref))
+            {
+                ranks.insert(rank);
+            }
+        }
+        match ranks.len() == k {
+            true => Some(ranks.into_iter().filter_map(|r| self.select(r)).collect()),
+            false => None,
+        }
+    }
+
+    fn is_counted(&self) -> bool {
+        self.root.as_deref().and_then(|n| n.agg.count()).is_some()
+    }
+
+    // Return number of entries for which `before` holds, such entries
+    // shall sort before the rest.
+    fn position<F>(&self, before: F) -> usize
+    where
+        F: Fn(&K) -> bool,
+    {
+        let (mut node, mut n) = (self.root.as_deref(), 0);
+        while let Some(nref) = node {
+            node = if before(&nref.key) {
+                n += to_count(nref.left_deref()) + 1;
+                nref.right_deref()
+            } else {
+                nref.left_deref()
+            };
+        }
+        n
+    }
+
+    // Return the entry at `rank`, in sort order.
+    fn select(&self, mut rank: usize) -> Option<&Node<K, V, A>> {
+        let mut node = self.root.as_deref();
+        while let Some(nref) = node {
+            let n = to_count(nref.left_deref());
+            node = match rank.cmp(&n) {
+                Ordering::Less => nref.left_deref(),
+                Ordering::Equal => return Some(nref),
+                Ordering::Greater => {
+                    rank -= n + 1;
+                    nref.right_deref()
+                }
+            };
+        }
+        None
+    }
+
+    // Walk entries from low to high, in sort order.
+    fn walk<'a, Q, R, F>(&'a self, range: &R, mut f: F)
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+        F: FnMut(&'a Node<K, V, A>),
+    {
+        let root = self.root.as_deref();
+        let mut paths = Vec::with_capacity(max_height(self.n_count));
+        match range.start_bound() {
+            Bound::Unbounded => build_iter(IFlag::Left, root, &mut paths),
+            Bound::Included(low) => find_start(root, low, true, &mut paths),
+            Bound::Excluded(low) => find_start(root, low, false, &mut paths),
+        };
+        while let Some(path) = paths.last_mut() {
+            match (path.flag, path.nref) {
+                (IFlag::Left, nref) if is_below(range, &nref.key) => {
+                    path.flag = IFlag::Center;
+                    f(nref)
+                }
+                (IFlag::Left, _) => break,
+                (IFlag::Center, nref) => {
+                    path.flag = IFlag::Right;
+                    build_iter(IFlag::Left, nref.right_deref(), &mut paths);
+                }
+                (_, _) => {
+                    paths.pop();
+                }
+            }
+        }
+    }
+}
+
+fn is_live<K, V, A>(live: Option<Live<K>>, nref: &Node<K, V, A>) -> bool
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    !matches!(live, Some(live) if !live.is_live(&nref.key))
+}
+
+fn to_count<K, V, A>(node: Option<&Node<K, V, A>>) -> usize
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    node.and_then(|nref| nref.agg.count()).unwrap_or(0)
+}
+
+#[cfg(test)]
+#[path = "sample_test.rs"]
+mod sample_test;
@@ -0,0 +1,212 @@
+use std::{collections::BTreeMap, ops::Bound, time::Duration};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::aggregate::Count;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_sample_uniform() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-sample");
+    let mut counted: Llrb<i64, i64, Count> = Llrb::new_aggregate("test-sample-count");
+    assert_eq!(llrb.sample_uniform(&mut rng), None);
+    assert_eq!(counted.sample_uniform(&mut rng), None);
+    for key in 0..100 {
+        llrb.set(key, key * 10);
+        counted.set(key, key * 10);
+    }
+
+    // every entry is picked, roughly the same number of times, unlike
+    // random() that favours entries near the root.
+    for n in [0, 1].iter() {
+        let mut hits: BTreeMap<i64, usize> = BTreeMap::new();
+        for _ in 0..100_000 {
+            let (key, value) = match n {
+                0 => llrb.sample_uniform(&mut rng).unwrap(),
+                _ => counted.sample_uniform(&mut rng).unwrap(),
+            };
+            assert_eq!(value, key * 10);
+            *hits.entry(key).or_default() += 1;
+        }
+        assert_eq!(hits.len(), 100);
+        for (key, n) in hits.into_iter() {
+            assert!(n > 700 && n < 1300, "key:{} hits:{}", key, n);
+        }
+    }
+}
+
+#[test]
+fn test_sample_range() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-sample-range");
+    let mut counted: Llrb<i64, i64, Count> = Llrb::new_aggregate("test-sample-range");
+    for _ in 0..1000 {
+        let key = rng.gen_range(0, 1000);
+        llrb.set(key, key);
+        counted.set(key, key);
+    }
+    for _ in 0..500 {
+        let key = rng.gen_range(0, 1000);
+        llrb.delete(&key);
+        counted.delete(&key);
+    }
+    assert_eq!(counted.fold_range::<i64, _>(..), Count(llrb.len()));
+
+    for _ in 0..1000 {
+        let (a, b) = (rng.gen_range(-10, 1010), rng.gen_range(-10, 1010));
+        let low = match rng.gen_range(0, 3) {
+            0 => Bound::Included(a),
+            1 => Bound::Excluded(a),
+            _ => Bound::Unbounded,
+        };
+        let high = match rng.gen_range(0, 3) {
+            0 => Bound::Included(b),
+            1 => Bound::Excluded(b),
+            _ => Bound::Unbounded,
+        };
+        let n = llrb.iter().filter(|(k, _)| (low, high).contains(k)).count();
+        for _ in 0..10 {
+            let items = [
+                llrb.sample_range((low, high), &mut rng),
+                counted.sample_range((low, high), &mut rng),
+            ];
+            for item in items.iter() {
+                match item {
+                    Some((key, _)) => assert!((low, high).contains(key)),
+                    None => assert_eq!(n, 0),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sample_k() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-sample-k");
+    let mut counted: Llrb<i64, i64, Count> = Llrb::new_aggregate("test-sample-k");
+    assert_eq!(llrb.sample_k(10, &mut rng), vec![]);
+    assert_eq!(counted.sample_k(10, &mut rng), vec![]);
+    for key in 0..20 {
+        llrb.set(key, key);
+        counted.set(key, key);
+    }
+    let all: Vec<(i64, i64)> = llrb.iter().collect();
+    assert_eq!(llrb.sample_k(100, &mut rng), all);
+    assert_eq!(counted.sample_k(100, &mut rng), all);
+    assert_eq!(llrb.sample_k(0, &mut rng), vec![]);
+    assert_eq!(counted.sample_k(0, &mut rng), vec![]);
+
+    for n in [0, 1].iter() {
+        let mut hits: BTreeMap<i64, usize> = BTreeMap::new();
+        for _ in 0..10_000 {
+            let items = match n {
+                0 => llrb.sample_k(5, &mut rng),
+                _ => counted.sample_k(5, &mut rng),
+            };
+            assert_eq!(items.len(), 5);
+            // distinct entries, in sort order.
+            assert!(items.windows(2).all(|w| w[0].0 < w[1].0));
+            items
+                .iter()
+                .for_each(|(key, _)| *hits.entry(*key).or_default() += 1);
+        }
+        assert_eq!(hits.len(), 20);
+        for (key, n) in hits.into_iter() {
+            assert!(n > 2000 && n < 3000, "key:{} hits:{}", key, n);
+        }
+    }
+}
+
+#[test]
+fn test_sample_weighted() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, f64> = Llrb::new("test-sample-weighted");
+    let weight = |_: &i64, w: &f64| *w;
+    assert_eq!(llrb.sample_weighted(weight, &mut rng), None);
+    llrb.set(0, 0.0);
+    llrb.set(1, -1.0);
+    llrb.set(2, f64::NAN);
+    assert_eq!(llrb.sample_weighted(weight, &mut rng), None);
+    llrb.set(3, 1.0);
+    llrb.set(4, 3.0);
+
+    let mut hits: BTreeMap<i64, usize> = BTreeMap::new();
+    for _ in 0..100_000 {
+        let (key, _) = llrb.sample_weighted(weight, &mut rng).unwrap();
+        *hits.entry(key).or_default() += 1;
+    }
+    assert_eq!(hits.keys().cloned().collect::<Vec<i64>>(), vec![3, 4]);
+    assert!(hits[&3] > 23_000 && hits[&3] < 27_000, "{:?}", hits);
+}
+
+#[test]
+fn test_sample_expired() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-sample-expired");
+    let mut counted: Llrb<i64, i64, Count> = Llrb::new_aggregate("test-sample-expired");
+    // all but the last 10 entries have expired, without being removed.
+    for key in 0..100 {
+        let ttl = Duration::from_secs(if key < 90 { 0 } else { 3600 });
+        llrb.set_with_ttl(key, key, ttl);
+        counted.set_with_ttl(key, key, ttl);
+    }
+
+    for n in [0, 1].iter() {
+        let mut hits: BTreeMap<i64, usize> = BTreeMap::new();
+        for _ in 0..1000 {
+            let (key, _) = match n {
+                0 => llrb.sample_uniform(&mut rng).unwrap(),
+                _ => counted.sample_uniform(&mut rng).unwrap(),
+            };
+            *hits.entry(key).or_default() += 1;
+        }
+        assert!(hits.keys().eq((90..100).collect::<Vec<i64>>().iter()));
+
+        let res = match n {
+            0 => llrb.sample_range(..95, &mut rng),
+            _ => counted.sample_range(..95, &mut rng),
+        };
+        assert!(res.unwrap().0 >= 90);
+        let res = match n {
+            0 => llrb.sample_range(..90, &mut rng),
+            _ => counted.sample_range(..90, &mut rng),
+        };
+        assert_eq!(res, None);
+
+        let (all, some) = match n {
+            0 => (llrb.sample_k(20, &mut rng), llrb.sample_k(5, &mut rng)),
+            _ => (
+                counted.sample_k(20, &mut rng),
+                counted.sample_k(5, &mut rng),
+            ),
+        };
+        assert!(all.into_iter().eq(llrb.iter()));
+        assert_eq!(some.len(), 5);
+        assert!(some.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(some.iter().all(|(key, _)| *key >= 90));
+
+        let res = match n {
+            0 => llrb.sample_weighted(|_, _| 1.0, &mut rng),
+            _ => counted.sample_weighted(|_, _| 1.0, &mut rng),
+        };
+        assert!(res.unwrap().0 >= 90);
+    }
+}
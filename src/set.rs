@@ -0,0 +1,372 @@
+use std::{borrow::Borrow, cmp::Ordering, iter::Peekable, ops::RangeBounds};
+
+use crate::empty::Empty;
+use crate::error::Error;
+use crate::llrb::{Iter, Llrb, Range, Reverse, Stats};
+
+/// LlrbSet is an ordered set of keys, backed by ``Llrb<K, Empty>``.
+///
+/// Along with the usual set operations, LlrbSet supports set algebra
+/// with another set, either as lazy iterators, that walk both sets in a
+/// single merge pass, or as in-place operations on this set.
+#[derive(Clone)]
+pub struct LlrbSet<K>
+where
+    K: Clone + Ord,
+{
+    llrb: Llrb<K, Empty>,
+}
+
+impl<K> Extend<K> for LlrbSet<K>
+where
+    K: Clone + Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = K>,
+    {
+        iter.into_iter().for_each(|key| {
+            self.insert(key);
+        });
+    }
+}
+
+impl<K> LlrbSet<K>
+where
+    K: Clone + Ord,
+{
+    /// Create an empty set, identified by `name`.
+    pub fn new<S>(name: S) -> LlrbSet<K>
+    where
+        S: AsRef<str>,
+    {
+        LlrbSet {
+            llrb: Llrb::new(name),
+        }
+    }
+
+    /// Identify this set.
+    pub fn id(&self) -> String {
+        self.llrb.id()
+    }
+
+    /// Return number of keys in this set.
+    pub fn len(&self) -> usize {
+        self.llrb.len()
+    }
+
+    /// Check whether this set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.llrb.is_empty()
+    }
+
+    /// Add `key` into this set. Return false if key was already present.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.llrb.set(key, Empty {}).is_none()
+    }
+
+    /// Check whether `key` is present in this set.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.llrb.contains_key(key)
+    }
+
+    /// Remove `key` from this set. Return false if key was not present.
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.llrb.delete(key).is_some()
+    }
+
+    /// Return an iterator over all keys in this set, in sort order.
+    pub fn iter(&self) -> Keys<Iter<'_, K, Empty>> {
+        Keys {
+            iter: self.llrb.iter(),
+        }
+    }
+
+    /// Return an iterator over keys from low to high.
+    pub fn range<Q, R>(&self, range: R) -> Keys<Range<'_, K, Empty, R, Q>>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Keys {
+            iter: self.llrb.range(range),
+        }
+    }
+
+    /// Return an iterator over keys from high to low.
+    pub fn reverse<Q, R>(&self, range: R) -> Keys<Reverse<'_, K, Empty, R, Q>>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Keys {
+            iter: self.llrb.reverse(range),
+        }
+    }
+
+    /// Validate the underlying tree, refer to [`Llrb::validate`].
+    pub fn validate(&self) -> Result<Stats, Error<K>> {
+        self.llrb.validate()
+    }
+}
+
+/// Set algebra.
+impl<K> LlrbSet<K>
+where
+    K: Clone + Ord,
+{
+    /// Return an iterator over keys present in this set or in `other`,
+    /// in sort order.
+    pub fn union<'a>(&'a self, other: &'a LlrbSet<K>) -> Union<'a, K> {
+        Union {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Return an iterator over keys present in both this set and
+    /// `other`, in sort order.
+    pub fn intersection<'a>(&'a self, other: &'a LlrbSet<K>) -> Intersection<'a, K> {
+        Intersection {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Return an iterator over keys present in this set but not in
+    /// `other`, in sort order.
+    pub fn difference<'a>(&'a self, other: &'a LlrbSet<K>) -> Difference<'a, K> {
+        Difference {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Return an iterator over keys present in either this set or
+    /// `other`, but not in both, in sort order.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a LlrbSet<K>) -> SymmetricDifference<'a, K> {
+        SymmetricDifference {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Add all keys from `other` into this set.
+    pub fn union_with(&mut self, other: &LlrbSet<K>) {
+        let keys: Vec<K> = other.difference(self).collect();
+        self.extend(keys);
+    }
+
+    /// Retain only those keys that are also present in `other`.
+    pub fn intersection_with(&mut self, other: &LlrbSet<K>) {
+        let keys: Vec<K> = self.difference(other).collect();
+        keys.iter().for_each(|key| {
+            self.remove(key);
+        });
+    }
+
+    /// Remove all keys that are present in `other`.
+    pub fn difference_with(&mut self, other: &LlrbSet<K>) {
+        let keys: Vec<K> = self.intersection(other).collect();
+        keys.iter().for_each(|key| {
+            self.remove(key);
+        });
+    }
+
+    /// Remove all keys that are present in `other`, and add all keys
+    /// from `other` that are not present in this set.
+    pub fn symmetric_difference_with(&mut self, other: &LlrbSet<K>) {
+        let items: Vec<(Side, K)> = Merge::new(self, other).collect();
+        for (side, key) in items.into_iter() {
+            match side {
+                Side::Left => (),
+                Side::Right => {
+                    self.insert(key);
+                }
+                Side::Both => {
+                    self.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Check whether all keys in this set are present in `other`.
+    pub fn is_subset(&self, other: &LlrbSet<K>) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    /// Check whether all keys in `other` are present in this set.
+    pub fn is_superset(&self, other: &LlrbSet<K>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Check whether this set and `other` have no keys in common.
+    pub fn is_disjoint(&self, other: &LlrbSet<K>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+pub struct Keys<I> {
+    iter: I,
+}
+
+impl<K, I> Iterator for Keys<I>
+where
+    I: Iterator<Item = (K, Empty)>,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.iter.next().map(|(key, _)| key)
+    }
+}
+
+// Set that a key, from a merge of two sets, belongs to.
+enum Side {
+    Left,
+    Right,
+    Both,
+}
+
+// Merge keys from two sets, in sort order, tagging each key with the
+// set it belongs to.
+struct Merge<'a, K>
+where
+    K: Clone + Ord,
+{
+    left: Peekable<Keys<Iter<'a, K, Empty>>>,
+    right: Peekable<Keys<Iter<'a, K, Empty>>>,
+}
+
+impl<'a, K> Merge<'a, K>
+where
+    K: Clone + Ord,
+{
+    fn new(left: &'a LlrbSet<K>, right: &'a LlrbSet<K>) -> Merge<'a, K> {
+        Merge {
+            left: left.iter().peekable(),
+            right: right.iter().peekable(),
+        }
+    }
+}
+
+impl<'a, K> Iterator for Merge<'a, K>
+where
+    K: Clone + Ord,
+{
+    type Item = (Side, K);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cmp = match (self.left.peek(), self.right.peek()) {
+            (Some(l), Some(r)) => l.cmp(r),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        match cmp {
+            Ordering::Less => Some((Side::Left, self.left.next()?)),
+            Ordering::Greater => Some((Side::Right, self.right.next()?)),
+            Ordering::Equal => {
+                self.right.next();
+                Some((Side::Both, self.left.next()?))
+            }
+        }
+    }
+}
+
+pub struct Union<'a, K>
+where
+    K: Clone + Ord,
+{
+    merge: Merge<'a, K>,
+}
+
+impl<'a, K> Iterator for Union<'a, K>
+where
+    K: Clone + Ord,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.merge.next().map(|(_, key)| key)
+    }
+}
+
+pub struct Intersection<'a, K>
+where
+    K: Clone + Ord,
+{
+    merge: Merge<'a, K>,
+}
+
+impl<'a, K> Iterator for Intersection<'a, K>
+where
+    K: Clone + Ord,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        loop {
+            match self.merge.next()? {
+                (Side::Both, key) => break Some(key),
+                _ => continue,
+            }
+        }
+    }
+}
+
+pub struct Difference<'a, K>
+where
+    K: Clone + Ord,
+{
+    merge: Merge<'a, K>,
+}
+
+impl<'a, K> Iterator for Difference<'a, K>
+where
+    K: Clone + Ord,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        loop {
+            match self.merge.next()? {
+                (Side::Left, key) => break Some(key),
+                _ => continue,
+            }
+        }
+    }
+}
+
+pub struct SymmetricDifference<'a, K>
+where
+    K: Clone + Ord,
+{
+    merge: Merge<'a, K>,
+}
+
+impl<'a, K> Iterator for SymmetricDifference<'a, K>
+where
+    K: Clone + Ord,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        loop {
+            match self.merge.next()? {
+                (Side::Left, key) | (Side::Right, key) => break Some(key),
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "set_test.rs"]
+mod set_test;
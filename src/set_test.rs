@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+#[test]
+fn test_set() {
+    let mut set: LlrbSet<i64> = LlrbSet::new("test-set");
+    assert_eq!(set.id(), "test-set".to_string());
+    assert!(set.is_empty());
+
+    assert!(set.insert(10));
+    assert!(set.insert(20));
+    assert!(!set.insert(10));
+    assert!(set.insert(30));
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&10));
+    assert!(!set.contains(&15));
+
+    assert!(set.remove(&20));
+    assert!(!set.remove(&20));
+    assert_eq!(set.iter().collect::<Vec<i64>>(), vec![10, 30]);
+
+    set.extend(vec![5, 15, 25, 35]);
+    assert_eq!(
+        set.range(10..=30).collect::<Vec<i64>>(),
+        vec![10, 15, 25, 30]
+    );
+    assert_eq!(set.reverse(..15).collect::<Vec<i64>>(), vec![10, 5]);
+    assert!(set.validate().is_ok());
+}
+
+#[test]
+fn test_set_algebra() {
+    let seed = make_seed();
+    println!("seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for _ in 0..100 {
+        let (mut a, mut b) = (LlrbSet::new("a"), LlrbSet::new("b"));
+        let (mut ra, mut rb) = (BTreeSet::new(), BTreeSet::new());
+        for _ in 0..rng.gen_range(0, 200) {
+            let key: i64 = rng.gen_range(0, 300);
+            assert_eq!(a.insert(key), ra.insert(key));
+        }
+        for _ in 0..rng.gen_range(0, 200) {
+            let key: i64 = rng.gen_range(0, 300);
+            assert_eq!(b.insert(key), rb.insert(key));
+        }
+
+        let refs: Vec<i64> = ra.union(&rb).cloned().collect();
+        assert_eq!(a.union(&b).collect::<Vec<i64>>(), refs);
+        let refs: Vec<i64> = ra.intersection(&rb).cloned().collect();
+        assert_eq!(a.intersection(&b).collect::<Vec<i64>>(), refs);
+        let refs: Vec<i64> = ra.difference(&rb).cloned().collect();
+        assert_eq!(a.difference(&b).collect::<Vec<i64>>(), refs);
+        let refs: Vec<i64> = ra.symmetric_difference(&rb).cloned().collect();
+        assert_eq!(a.symmetric_difference(&b).collect::<Vec<i64>>(), refs);
+
+        assert_eq!(a.is_subset(&b), ra.is_subset(&rb));
+        assert_eq!(a.is_superset(&b), ra.is_superset(&rb));
+        assert_eq!(a.is_disjoint(&b), ra.is_disjoint(&rb));
+        let c: LlrbSet<i64> = {
+            let mut c = LlrbSet::new("c");
+            c.extend(a.intersection(&b));
+            c
+        };
+        assert!(c.is_subset(&a) && c.is_subset(&b));
+        assert!(a.is_superset(&c));
+
+        let mut x = a.clone();
+        x.union_with(&b);
+        assert!(x.iter().eq(ra.union(&rb).cloned()));
+        let mut x = a.clone();
+        x.intersection_with(&b);
+        assert!(x.iter().eq(ra.intersection(&rb).cloned()));
+        let mut x = a.clone();
+        x.difference_with(&b);
+        assert!(x.iter().eq(ra.difference(&rb).cloned()));
+        let mut x = a.clone();
+        x.symmetric_difference_with(&b);
+        assert!(x.iter().eq(ra.symmetric_difference(&rb).cloned()));
+        assert!(x.validate().is_ok());
+
+        a.union_with(&b);
+        ra = ra.union(&rb).cloned().collect();
+        assert!(a.iter().eq(ra.iter().cloned()));
+        rb.clear();
+        b = LlrbSet::new("b");
+        assert!(a.is_disjoint(&b) && b.is_subset(&a));
+    }
+}
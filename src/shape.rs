@@ -0,0 +1,146 @@
+use crate::aggregate::Aggregate;
+use crate::error::Error;
+use crate::llrb::{Llrb, Node};
+
+/// Shape captures the exact structure of a [`Llrb`] tree, its entries,
+/// colours and links, to replay a broken tree in tests. Refer to
+/// [`Llrb::to_shape`] and [`Llrb::from_shape`].
+///
+/// Nodes are held flat, in pre-order, and link to their children by
+/// offset into `nodes`. With the `serde` feature, shape can be serialized
+/// and deserialized via serde.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shape<K, V> {
+    /// Offset of the root node, None for an empty tree.
+    pub root: Option<usize>,
+    /// Nodes in the tree.
+    pub nodes: Vec<ShapeNode<K, V>>,
+}
+
+/// Single node in a [`Shape`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeNode<K, V> {
+    pub key: K,
+    pub value: V,
+    pub black: bool,
+    /// Offset of the left child.
+    pub left: Option<usize>,
+    /// Offset of the right child.
+    pub right: Option<usize>,
+}
+
+/// Shape export and import on Llrb instance.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Return the exact shape of this tree.
+    pub fn to_shape(&self) -> Shape<K, V> {
+        let mut nodes: Vec<ShapeNode<K, V>> = Vec::with_capacity(self.n_count);
+        // node to visit, along with its parent offset and side.
+        let mut stack = vec![];
+        if let Some(root) = self.root.as_deref() {
+            stack.push((root, None::<(usize, bool)>));
+        }
+        while let Some((nref, parent)) = stack.pop() {
+            let off = nodes.len();
+            match parent {
+                Some((poff, true)) => nodes[poff].left = Some(off),
+                Some((poff, false)) => nodes[poff].right = Some(off),
+                None => (),
+            }
+            nodes.push(ShapeNode {
+                key: nref.key.clone(),
+                value: nref.value.clone(),
+                black: nref.black,
+                left: None,
+                right: None,
+            });
+            if let Some(right) = nref.right_deref() {
+                stack.push((right, Some((off, false))));
+            }
+            if let Some(left) = nref.left_deref() {
+                stack.push((left, Some((off, true))));
+            }
+        }
+        let root = if nodes.is_empty() { None } else { Some(0) };
+        Shape { root, nodes }
+    }
+
+    /// Create an instance of Llrb, identified by `name`, with the exact
+    /// tree captured by `shape`. Tree is loaded as is, without checking
+    /// for sort order or colours, so that an invalid tree can be replayed
+    /// and checked via [`Llrb::validate`]. Sub-tree aggregates are
+    /// computed afresh.
+    ///
+    /// Return [`Error::InvalidShape`] if links do not form a single tree
+    /// rooted at `shape.root`, that is, a link is out of bounds, a node
+    /// has more than one parent or a node is not reachable from root.
+    pub fn from_shape<S>(name: S, shape: Shape<K, V>) -> Result<Llrb<K, V, A>, Error<K>>
+    where
+        S: AsRef<str>,
+    {
+        let n = shape.nodes.len();
+        let mut parents = vec![false; n];
+        if let Some(root) = shape.root {
+            match parents.get_mut(root) {
+                Some(parent) => *parent = true,
+                None => return Err(Error::InvalidShape { node: root }),
+            }
+        }
+        for (off, snode) in shape.nodes.iter().enumerate() {
+            for child in [snode.left, snode.right].iter().filter_map(|c| *c) {
+                match parents.get_mut(child) {
+                    Some(true) => return Err(Error::InvalidShape { node: child }),
+                    Some(parent) => *parent = true,
+                    None => return Err(Error::InvalidShape { node: off }),
+                }
+            }
+        }
+        // with a single parent for every node, nodes reachable from root
+        // form a tree, list them in pre-order.
+        let mut order = Vec::with_capacity(n);
+        let mut stack: Vec<usize> = shape.root.into_iter().collect();
+        while let Some(off) = stack.pop() {
+            order.push(off);
+            let snode = &shape.nodes[off];
+            stack.extend(snode.right.iter().chain(snode.left.iter()));
+        }
+        if order.len() < n {
+            let mut reached = vec![false; n];
+            order.iter().for_each(|off| reached[*off] = true);
+            let node = reached.iter().position(|r| !r).unwrap();
+            return Err(Error::InvalidShape { node });
+        }
+
+        // build bottom-up, children before their parent.
+        let links: Vec<(Option<usize>, Option<usize>)> =
+            shape.nodes.iter().map(|s| (s.left, s.right)).collect();
+        let mut slots: Vec<Option<Box<Node<K, V, A>>>> = shape
+            .nodes
+            .into_iter()
+            .map(|s| Some(Node::new(s.key, s.value, s.black)))
+            .collect();
+        for off in order.into_iter().rev() {
+            let mut node = slots[off].take().unwrap();
+            let (left, right) = links[off];
+            node.left = left.and_then(|l| slots[l].take());
+            node.right = right.and_then(|r| slots[r].take());
+            node.repair();
+            slots[off] = Some(node);
+        }
+
+        let mut llrb = Llrb::new_aggregate(name);
+        llrb.root = shape.root.and_then(|root| slots[root].take());
+        llrb.n_count = n;
+        Ok(llrb)
+    }
+}
+
+#[cfg(test)]
+#[path = "shape_test.rs"]
+mod shape_test;
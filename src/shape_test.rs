@@ -0,0 +1,133 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::aggregate::Count;
+use crate::test_util::make_seed;
+
+fn snode(key: i64, black: bool, left: Option<usize>, right: Option<usize>) -> ShapeNode<i64, i64> {
+    ShapeNode {
+        key,
+        value: key * 10,
+        black,
+        left,
+        right,
+    }
+}
+
+#[test]
+fn test_shape() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64, Count> = Llrb::new_aggregate("test-shape");
+    let shape = llrb.to_shape();
+    assert_eq!(
+        shape,
+        Shape {
+            root: None,
+            nodes: vec![]
+        }
+    );
+    let loaded: Llrb<i64, i64, Count> = Llrb::from_shape("test-shape", shape).unwrap();
+    assert!(loaded.is_empty());
+
+    for _ in 0..1000 {
+        let key = rng.gen_range(0, 1000);
+        llrb.set(key, key);
+    }
+    for _ in 0..500 {
+        let key = rng.gen_range(0, 1000);
+        llrb.delete(&key);
+    }
+
+    let shape = llrb.to_shape();
+    assert_eq!(shape.root, Some(0));
+    assert_eq!(shape.nodes.len(), llrb.len());
+    let loaded: Llrb<i64, i64, Count> = Llrb::from_shape("loaded", shape.clone()).unwrap();
+    assert_eq!(loaded.to_shape(), shape);
+    assert_eq!(loaded.len(), llrb.len());
+    assert!(loaded.iter().eq(llrb.iter()));
+    assert_eq!(loaded.pretty_tree(), llrb.pretty_tree());
+    assert_eq!(loaded.fold_range::<i64, _>(..), Count(llrb.len()));
+    assert_eq!(loaded.fold_range(100..200), llrb.fold_range(100..200));
+    assert!(loaded.validate().is_ok());
+}
+
+#[test]
+fn test_shape_invalid_tree() {
+    // red root, with a red right link, and keys out of sort order.
+    let shape = Shape {
+        root: Some(0),
+        nodes: vec![
+            snode(20, false, Some(1), Some(2)),
+            snode(30, true, None, None),
+            snode(40, false, None, None),
+        ],
+    };
+    let llrb: Llrb<i64, i64> = Llrb::from_shape("test-shape-invalid", shape.clone()).unwrap();
+    assert_eq!(llrb.to_shape(), shape);
+    assert_eq!(llrb.len(), 3);
+    assert_eq!(llrb.validate().err(), Some(Error::RedRoot));
+
+    let report = llrb.validate_report();
+    assert!(!report.is_ok());
+    let errors: Vec<&Error<i64>> = report.violations().iter().map(|v| v.error()).collect();
+    assert!(errors.contains(&&Error::RedRoot), "{:?}", errors);
+    assert!(errors.contains(&&Error::RedRightLink), "{:?}", errors);
+    assert!(errors.contains(&&Error::SortError(30, 20)), "{:?}", errors);
+}
+
+#[test]
+fn test_shape_invalid_links() {
+    let load = |root, nodes| -> Result<Llrb<i64, i64>, Error<i64>> {
+        Llrb::from_shape("test-shape-links", Shape { root, nodes })
+    };
+
+    // root out of bounds.
+    let res = load(Some(1), vec![snode(10, true, None, None)]);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 1 }));
+    // child out of bounds.
+    let res = load(Some(0), vec![snode(10, true, None, Some(5))]);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 0 }));
+    // shared child.
+    let nodes = vec![
+        snode(10, true, Some(1), Some(2)),
+        snode(5, true, None, Some(2)),
+        snode(15, true, None, None),
+    ];
+    let res = load(Some(0), nodes);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 2 }));
+    // link back to root.
+    let nodes = vec![
+        snode(10, true, Some(1), None),
+        snode(5, true, Some(0), None),
+    ];
+    let res = load(Some(0), nodes);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 0 }));
+    // cycle, unreachable from root.
+    let nodes = vec![
+        snode(10, true, None, None),
+        snode(5, true, Some(2), None),
+        snode(15, true, Some(1), None),
+    ];
+    let res = load(Some(0), nodes);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 1 }));
+    // nodes without root.
+    let res = load(None, vec![snode(10, true, None, None)]);
+    assert_eq!(res.err(), Some(Error::InvalidShape { node: 0 }));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_shape_serde() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-shape-serde");
+    (1..=10).for_each(|key| {
+        llrb.set(key, key);
+    });
+    let shape = llrb.to_shape();
+    let s = serde_json::to_string(&shape).unwrap();
+    let shape: Shape<i64, i64> = serde_json::from_str(&s).unwrap();
+    let loaded: Llrb<i64, i64> = Llrb::from_shape("loaded", shape).unwrap();
+    assert_eq!(loaded.pretty_tree(), llrb.pretty_tree());
+}
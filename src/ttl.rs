@@ -0,0 +1,131 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    time::Instant,
+};
+
+/// Expiry book-keeps deadlines for entries set with a time-to-live in
+/// [`Llrb`](crate::Llrb) instance. Deadlines are indexed by key, to look
+/// up an entry's deadline, and by deadline, to sweep expired entries
+/// without a full scan of the tree.
+#[derive(Clone)]
+pub(crate) struct Expiry<K>
+where
+    K: Clone + Ord,
+{
+    deadlines: BTreeMap<K, Instant>,
+    index: BTreeSet<(Instant, K)>,
+}
+
+impl<K> Default for Expiry<K>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Expiry {
+            deadlines: BTreeMap::new(),
+            index: BTreeSet::new(),
+        }
+    }
+}
+
+impl<K> Expiry<K>
+where
+    K: Clone + Ord,
+{
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    pub(crate) fn set(&mut self, key: K, deadline: Instant) {
+        if let Some(old) = self.deadlines.insert(key.clone(), deadline) {
+            self.index.remove(&(old, key.clone()));
+        }
+        self.index.insert((deadline, key));
+    }
+
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<Instant>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (key, deadline) = self.deadlines.remove_entry(key)?;
+        self.index.remove(&(deadline, key));
+        Some(deadline)
+    }
+
+    pub(crate) fn deadline<Q>(&self, key: &Q) -> Option<Instant>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.deadlines.get(key).cloned()
+    }
+
+    pub(crate) fn is_expired<Q>(&self, key: &Q, now: Instant) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.deadlines
+            .get(key)
+            .is_some_and(|deadline| *deadline <= now)
+    }
+
+    /// Pop the key with the earliest deadline, if it is expired as of
+    /// `now`. Items in the index that have drifted from `deadlines`, due
+    /// to a panic midway through book-keeping, are dropped on the way.
+    pub(crate) fn pop_expired(&mut self, now: Instant) -> Option<K> {
+        loop {
+            let (deadline, key) = match self.index.iter().next() {
+                Some(item) if item.0 <= now => item.clone(),
+                _ => break None,
+            };
+            self.index.remove(&(deadline, key.clone()));
+            if self.deadlines.get(&key) == Some(&deadline) {
+                self.deadlines.remove(&key);
+                break Some(key);
+            }
+        }
+    }
+}
+
+/// Live filters out entries whose deadline has lapsed, as of `now`,
+/// while reading from [`Llrb`](crate::Llrb) instance.
+pub(crate) struct Live<'a, K>
+where
+    K: Clone + Ord,
+{
+    expiry: &'a Expiry<K>,
+    now: Instant,
+}
+
+impl<'a, K> Clone for Live<'a, K>
+where
+    K: Clone + Ord,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, K> Copy for Live<'a, K> where K: Clone + Ord {}
+
+impl<'a, K> Live<'a, K>
+where
+    K: Clone + Ord,
+{
+    pub(crate) fn new(expiry: &'a Expiry<K>, now: Instant) -> Option<Live<'a, K>> {
+        if expiry.is_empty() {
+            None
+        } else {
+            Some(Live { expiry, now })
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_live(&self, key: &K) -> bool {
+        !self.expiry.is_expired(key, self.now)
+    }
+}
@@ -0,0 +1,191 @@
+use std::{borrow::Borrow, ops::Deref, time::Instant};
+
+use crate::error::Error;
+use crate::evict::Position;
+use crate::llrb::Llrb;
+
+// Prior state of an entry, before it was written within a transaction.
+struct Undo<K, V> {
+    key: K,
+    // value along with its deadline, None if the entry was absent.
+    entry: Option<(V, Option<Instant>)>,
+    // position in access order, for bounded instances.
+    position: Option<Position>,
+    // number of expired entries in the index.
+    n_expired: usize,
+    // number of changes held back by the observer.
+    n_held: usize,
+}
+
+/// Savepoint marks a position in the undo log of a [`Txn`], refer to
+/// [`Txn::savepoint`] and [`Txn::rollback_to`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Savepoint(usize);
+
+/// Txn is a transaction over a [`Llrb`] instance, created via
+/// [`Llrb::begin`].
+///
+/// Write operations are applied on the index as they happen, so that
+/// the transaction can read its own writes, and recorded in an undo log.
+/// Dropping the transaction without a [`Txn::commit`] shall rollback all
+/// its writes. Bounded instances defer eviction until commit, so that
+/// a rollback restores the exact prior contents of the index.
+///
+/// Changes are published to observers, watches and the change log, refer
+/// to [`Llrb::observe`], only on commit. Changes that are rolled back are
+/// never published. Sequence numbers advance on commit.
+///
+/// Read operations are available on the transaction, via [`Deref`].
+pub struct Txn<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    llrb: &'a mut Llrb<K, V>,
+    undo: Vec<Undo<K, V>>,
+    done: bool,
+}
+
+/// Transaction API.
+impl<K, V> Llrb<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Begin a transaction on this instance.
+    pub fn begin(&mut self) -> Txn<'_, K, V> {
+        self.observer.hold();
+        Txn {
+            llrb: self,
+            undo: vec![],
+            done: false,
+        }
+    }
+}
+
+impl<'a, K, V> Deref for Txn<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    type Target = Llrb<K, V>;
+
+    fn deref(&self) -> &Llrb<K, V> {
+        self.llrb
+    }
+}
+
+impl<'a, K, V> Drop for Txn<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    fn drop(&mut self) {
+        if !self.done {
+            self.rollback_to(Savepoint(0))
+        }
+        self.llrb.observer.release(false);
+    }
+}
+
+impl<'a, K, V> Txn<'a, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Same as [`Llrb::create`], within this transaction.
+    pub fn create(&mut self, key: K, value: V) -> Result<(), Error<K>> {
+        let undo = self.prior(&key);
+        self.llrb.do_create(key, value)?;
+        self.undo.push(undo);
+        Ok(())
+    }
+
+    /// Same as [`Llrb::set`], within this transaction.
+    pub fn set(&mut self, key: K, value: V) -> Option<V> {
+        let undo = self.prior(&key);
+        self.undo.push(undo);
+        self.llrb.do_set(key, value, None)
+    }
+
+    /// Same as [`Llrb::delete`], within this transaction.
+    pub fn delete<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (key, value, deadline) = self.llrb.entry(key)?;
+        let undo = Undo {
+            position: self.llrb.access_position::<K>(&key),
+            key: key.clone(),
+            entry: Some((value, deadline)),
+            n_expired: self.llrb.n_expired,
+            n_held: self.llrb.observer.n_held(),
+        };
+        self.undo.push(undo);
+        self.llrb.delete::<K>(&key)
+    }
+
+    /// Mark current position in the undo log. Writes after this
+    /// savepoint can be rolled back without abandoning the transaction.
+    /// Savepoints can be nested.
+    pub fn savepoint(&self) -> Savepoint {
+        Savepoint(self.undo.len())
+    }
+
+    /// Rollback all writes since `savepoint`. Savepoints marked after
+    /// `savepoint` are discarded, rolling back to them is a no-op.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) {
+        while self.undo.len() > savepoint.0 {
+            let undo = self.undo.pop().unwrap();
+            match undo.entry {
+                Some((value, deadline)) => {
+                    let key = undo.key.clone();
+                    self.llrb.do_set(undo.key, value, deadline);
+                    if let Some(position) = undo.position {
+                        self.llrb.reposition(&key, position);
+                    }
+                }
+                None => {
+                    self.llrb.remove(&undo.key);
+                }
+            }
+            self.llrb.n_expired = undo.n_expired;
+            // drop the change, along with those undoing it.
+            self.llrb.observer.truncate(undo.n_held);
+        }
+    }
+
+    /// Commit all writes in this transaction. Bounded instances shall
+    /// evict entries, if the index exceeds its capacity.
+    pub fn commit(mut self) {
+        self.done = true;
+        self.llrb.observer.release(true);
+        self.llrb.evict();
+    }
+
+    /// Rollback all writes in this transaction, restoring the exact
+    /// contents of the index as it was when the transaction began.
+    pub fn rollback(mut self) {
+        self.rollback_to(Savepoint(0));
+        self.done = true;
+    }
+
+    fn prior(&self, key: &K) -> Undo<K, V> {
+        let entry = self
+            .llrb
+            .entry(key)
+            .map(|(_, value, deadline)| (value, deadline));
+        Undo {
+            key: key.clone(),
+            position: entry.as_ref().and_then(|_| self.llrb.access_position(key)),
+            entry,
+            n_expired: self.llrb.n_expired,
+            n_held: self.llrb.observer.n_held(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "txn_test.rs"]
+mod txn_test;
@@ -0,0 +1,223 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::*;
+use crate::evict::{Capacity, Eviction};
+use crate::observe::Event;
+
+#[test]
+fn test_txn_commit() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let mut txn = llrb.begin();
+    assert_eq!(txn.set(1, 10), Some(1));
+    assert_eq!(txn.create(10, 100), Ok(()));
+    assert_eq!(txn.create(2, 20), Err(Error::OverwriteKey));
+    assert_eq!(txn.delete(&3), Some(3));
+    assert_eq!(txn.delete(&30), None);
+    // read own writes.
+    assert_eq!(txn.get(&1), Some(10));
+    assert_eq!(txn.get(&3), None);
+    assert_eq!(txn.len(), 10);
+    txn.commit();
+
+    let keys: Vec<i64> = llrb.iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![0, 1, 2, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(llrb.get(&1), Some(10));
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_txn_rollback() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..100).for_each(|key| assert!(llrb.set(key, key).is_none()));
+    assert!(llrb
+        .set_with_ttl(100, 100, Duration::from_secs(3600))
+        .is_none());
+    let refs: Vec<(i64, i64)> = llrb.iter().collect();
+
+    let mut txn = llrb.begin();
+    for key in 0..200 {
+        match key % 3 {
+            0 => {
+                txn.set(key, -key);
+            }
+            1 => {
+                txn.create(key, -key).ok();
+            }
+            _ => {
+                txn.delete(&key);
+            }
+        }
+    }
+    txn.delete(&100);
+    txn.rollback();
+
+    assert_eq!(llrb.iter().collect::<Vec<(i64, i64)>>(), refs);
+    assert_eq!(llrb.len(), 101);
+    assert_eq!(
+        llrb.expire(std::time::Instant::now() + Duration::from_secs(7200), 10),
+        1
+    );
+    assert!(llrb.validate().is_ok());
+
+    // drop shall rollback.
+    {
+        let mut txn = llrb.begin();
+        txn.set(1000, 1000);
+        txn.delete(&0);
+    }
+    assert_eq!(llrb.get(&1000), None);
+    assert_eq!(llrb.get(&0), Some(0));
+    assert_eq!(llrb.len(), 100);
+}
+
+#[test]
+fn test_txn_savepoint() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let mut txn = llrb.begin();
+    txn.set(1, 10);
+    let sp1 = txn.savepoint();
+    txn.set(2, 20);
+    txn.delete(&3);
+    let sp2 = txn.savepoint();
+    txn.set(2, 200);
+    txn.create(11, 110).unwrap();
+
+    txn.rollback_to(sp2);
+    assert_eq!(txn.get(&2), Some(20));
+    assert_eq!(txn.get(&11), None);
+    assert_eq!(txn.len(), 9);
+
+    txn.set(12, 120);
+    txn.rollback_to(sp1);
+    assert_eq!(txn.get(&2), Some(2));
+    assert_eq!(txn.get(&3), Some(3));
+    assert_eq!(txn.get(&12), None);
+    assert_eq!(txn.get(&1), Some(10));
+    txn.rollback_to(sp2); // discarded savepoint
+    txn.commit();
+
+    let mut refs: Vec<(i64, i64)> = (0..10).map(|key| (key, key)).collect();
+    refs[1] = (1, 10);
+    assert_eq!(llrb.iter().collect::<Vec<(i64, i64)>>(), refs);
+    assert!(llrb.validate().is_ok());
+}
+
+#[test]
+fn test_txn_bounded() {
+    let capacity = Capacity::Entries(10);
+    let mut llrb: Llrb<i64, i64> = Llrb::new_bounded("test-llrb", capacity, Eviction::Oldest);
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    let mut txn = llrb.begin();
+    (10..15).for_each(|key| assert!(txn.set(key, key).is_none()));
+    assert_eq!(txn.len(), 15);
+    txn.rollback();
+    assert_eq!(llrb.len(), 10);
+    assert!(llrb.drain_evicted().is_empty());
+
+    let mut txn = llrb.begin();
+    (10..15).for_each(|key| assert!(txn.set(key, key).is_none()));
+    txn.commit();
+    assert_eq!(llrb.len(), 10);
+    let evicted: Vec<i64> = llrb.drain_evicted().into_iter().map(|(k, _)| k).collect();
+    assert_eq!(evicted, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_txn_rollback_order() {
+    let capacity = Capacity::Entries(3);
+    let mut llrb: Llrb<i64, i64> = Llrb::new_bounded("test-llrb", capacity, Eviction::Lru);
+    (1..=3).for_each(|key| assert!(llrb.set(key, key).is_none()));
+
+    // rollback shall restore the access order.
+    let mut txn = llrb.begin();
+    assert_eq!(txn.delete(&1), Some(1));
+    assert_eq!(txn.set(2, 20), Some(2));
+    txn.rollback();
+    assert!(llrb.set(4, 4).is_none());
+    assert_eq!(llrb.drain_evicted(), vec![(1, 1)]);
+    assert!(llrb.set(5, 5).is_none());
+    assert_eq!(llrb.drain_evicted(), vec![(2, 2)]);
+
+    // rollback shall restore the count of expired entries.
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    assert!(llrb.set_with_ttl(1, 1, Duration::from_secs(0)).is_none());
+    assert!(llrb.set_with_ttl(2, 2, Duration::from_secs(0)).is_none());
+    let mut txn = llrb.begin();
+    assert_eq!(txn.create(1, 10), Ok(()));
+    assert_eq!(txn.set(2, 20), None);
+    assert_eq!(txn.stats().expired(), 2);
+    txn.rollback();
+    assert_eq!(llrb.stats().expired(), 0);
+    assert_eq!(llrb.len(), 2);
+    assert_eq!(llrb.expire(std::time::Instant::now(), 10), 2);
+    assert_eq!(llrb.stats().expired(), 2);
+}
+
+#[test]
+fn test_txn_observe() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-llrb");
+    (0..10).for_each(|key| assert!(llrb.set(key, key).is_none()));
+    llrb.set_change_log(100);
+    let events = Arc::new(Mutex::new(vec![]));
+    let evs = Arc::clone(&events);
+    llrb.observe(move |event| evs.lock().unwrap().push(event.clone()));
+    let watched = Arc::new(Mutex::new(vec![]));
+    let evs = Arc::clone(&watched);
+    llrb.watch(.., move |event: &Event<i64, i64>| {
+        evs.lock().unwrap().push(event.clone())
+    });
+    let seqno = llrb.seqno();
+
+    // changes are not published until commit.
+    let mut txn = llrb.begin();
+    assert_eq!(txn.set(1, 10), Some(1));
+    let sp = txn.savepoint();
+    assert_eq!(txn.delete(&2), Some(2));
+    assert_eq!(txn.create(20, 20), Ok(()));
+    assert_eq!(txn.seqno(), seqno);
+    assert!(events.lock().unwrap().is_empty());
+    assert!(watched.lock().unwrap().is_empty());
+    // changes rolled back are never published.
+    txn.rollback_to(sp);
+    assert_eq!(txn.set(3, 30), Some(3));
+    txn.commit();
+
+    let refs = vec![Event::Updated(1, 1, 10), Event::Updated(3, 3, 30)];
+    assert_eq!(*events.lock().unwrap(), refs);
+    assert_eq!(*watched.lock().unwrap(), refs);
+    assert_eq!(llrb.seqno(), seqno + 2);
+    let changes: Vec<Event<i64, i64>> = llrb
+        .drain_changes(seqno)
+        .unwrap()
+        .into_iter()
+        .map(|(_, event)| event)
+        .collect();
+    assert_eq!(changes, refs);
+
+    // nothing is published on rollback, or on drop.
+    let mut txn = llrb.begin();
+    assert_eq!(txn.set(4, 40), Some(4));
+    assert_eq!(txn.delete(&5), Some(5));
+    txn.rollback();
+    {
+        let mut txn = llrb.begin();
+        assert_eq!(txn.create(30, 30), Ok(()));
+    }
+    assert_eq!(events.lock().unwrap().len(), 2);
+    assert_eq!(watched.lock().unwrap().len(), 2);
+    assert_eq!(llrb.seqno(), seqno + 2);
+    assert_eq!(llrb.drain_changes(seqno + 2), Ok(vec![]));
+    assert!(llrb.validate().is_ok());
+
+    // writes after the transaction are published right away.
+    assert!(llrb.set(40, 40).is_none());
+    assert_eq!(events.lock().unwrap().len(), 3);
+}
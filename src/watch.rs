@@ -0,0 +1,203 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::aggregate::Aggregate;
+use crate::interval::IntervalLlrb;
+use crate::llrb::Llrb;
+use crate::observe::{Event, ObserverFn};
+
+/// Identify a watch registered on [`Llrb`] instance, refer to
+/// [`Llrb::watch`] and [`Llrb::unwatch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchId(u64);
+
+// Edge of a key range, such that any range of keys can be indexed as a
+// half-open interval, `[start, end)`, of edges. A key is located at the
+// edge just below it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Edge<K> {
+    Min,
+    Below(K),
+    Above(K),
+    Max,
+}
+
+impl<K> Edge<K>
+where
+    K: Clone + Ord,
+{
+    fn to_start(bound: Bound<&K>) -> Edge<K> {
+        match bound {
+            Bound::Included(key) => Edge::Below(key.clone()),
+            Bound::Excluded(key) => Edge::Above(key.clone()),
+            Bound::Unbounded => Edge::Min,
+        }
+    }
+
+    fn to_end(bound: Bound<&K>) -> Edge<K> {
+        match bound {
+            Bound::Included(key) => Edge::Above(key.clone()),
+            Bound::Excluded(key) => Edge::Below(key.clone()),
+            Bound::Unbounded => Edge::Max,
+        }
+    }
+}
+
+impl<K> PartialOrd for Edge<K>
+where
+    K: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for Edge<K>
+where
+    K: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Edge::Min, Edge::Min) | (Edge::Max, Edge::Max) => Ordering::Equal,
+            (Edge::Min, _) | (_, Edge::Max) => Ordering::Less,
+            (_, Edge::Min) | (Edge::Max, _) => Ordering::Greater,
+            (Edge::Below(a), Edge::Below(b)) | (Edge::Above(a), Edge::Above(b)) => a.cmp(b),
+            (Edge::Below(a), Edge::Above(b)) => a.cmp(b).then(Ordering::Less),
+            (Edge::Above(a), Edge::Below(b)) => a.cmp(b).then(Ordering::Greater),
+        }
+    }
+}
+
+type Interval<K> = (Edge<K>, Edge<K>);
+
+// Index of watched ranges. Type erased, so that the interval index,
+// itself built on Llrb, does not nest an index of its own.
+trait Stab<K>: Send + Sync {
+    fn insert(&mut self, interval: Interval<K>, id: WatchId);
+
+    fn remove(&mut self, interval: &Interval<K>, id: WatchId);
+
+    // Return watches whose range contain `key`.
+    fn stabbing(&self, key: &K) -> Vec<WatchId>;
+}
+
+impl<K> Stab<K> for IntervalLlrb<Edge<K>, Vec<WatchId>>
+where
+    K: Clone + Ord + Send + Sync,
+{
+    fn insert(&mut self, (start, end): Interval<K>, id: WatchId) {
+        let mut ids = self.get(&start, &end).unwrap_or_default();
+        ids.push(id);
+        IntervalLlrb::insert(self, start, end, ids);
+    }
+
+    fn remove(&mut self, (start, end): &Interval<K>, id: WatchId) {
+        let mut ids = self.get(start, end).unwrap_or_default();
+        ids.retain(|x| *x != id);
+        match ids.is_empty() {
+            true => self.delete(start, end),
+            false => IntervalLlrb::insert(self, start.clone(), end.clone(), ids),
+        };
+    }
+
+    fn stabbing(&self, key: &K) -> Vec<WatchId> {
+        let point = Edge::Below(key.clone());
+        IntervalLlrb::stabbing(self, point)
+            .flat_map(|(_, ids)| ids)
+            .collect()
+    }
+}
+
+// Watches dispatch events to watchers, whose range contain the event's
+// key. Watched ranges are indexed as intervals, dispatch shall cost
+// O(log w) for `w` watches, plus the number of matching watches.
+pub(crate) struct Watches<K, V> {
+    next: u64,
+    index: Option<Box<dyn Stab<K>>>,
+    watchers: BTreeMap<WatchId, (Interval<K>, ObserverFn<K, V>)>,
+}
+
+impl<K, V> Default for Watches<K, V> {
+    fn default() -> Self {
+        Watches {
+            next: 0,
+            index: None,
+            watchers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, V> Watches<K, V>
+where
+    K: Clone + Ord,
+{
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.watchers.is_empty()
+    }
+
+    pub(crate) fn dispatch(&mut self, event: &Event<K, V>) {
+        let ids = match (event.key(), self.index.as_ref()) {
+            (Some(key), Some(index)) => index.stabbing(key),
+            (Some(_), None) => vec![],
+            (None, _) => self.watchers.keys().cloned().collect(),
+        };
+        for id in ids {
+            let (_, watcher) = self.watchers.get_mut(&id).unwrap();
+            watcher(event)
+        }
+    }
+}
+
+/// Key-range watches on Llrb instance.
+impl<K, V, A> Llrb<K, V, A>
+where
+    K: Clone + Ord + Send + Sync + 'static,
+    V: Clone,
+    A: Aggregate<K, V>,
+{
+    /// Add a watch on `range` of keys, `watcher` shall be called with an
+    /// [`Event`] for every mutation on a key within `range`, and on
+    /// [`Event::Cleared`]. Same as [`Llrb::observe`], watches are not
+    /// cloned along with the instance.
+    pub fn watch<R, F>(&mut self, range: R, watcher: F) -> WatchId
+    where
+        R: RangeBounds<K>,
+        F: 'static + FnMut(&Event<K, V>) + Send + Sync,
+    {
+        let watches = &mut self.observer.watches;
+        let id = WatchId(watches.next);
+        watches.next += 1;
+
+        let start = Edge::to_start(range.start_bound());
+        let end = Edge::to_end(range.end_bound());
+        let index = watches.index.get_or_insert_with(|| {
+            let index: IntervalLlrb<Edge<K>, Vec<WatchId>> = IntervalLlrb::new("watches");
+            Box::new(index)
+        });
+        index.insert((start.clone(), end.clone()), id);
+        watches
+            .watchers
+            .insert(id, ((start, end), Box::new(watcher)));
+        id
+    }
+
+    /// Remove watch `id`. Return false if there is no such watch.
+    pub fn unwatch(&mut self, id: WatchId) -> bool {
+        let watches = &mut self.observer.watches;
+        match watches.watchers.remove(&id) {
+            Some((interval, _)) => {
+                watches.index.as_mut().unwrap().remove(&interval, id);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "watch_test.rs"]
+mod watch_test;
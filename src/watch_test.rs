@@ -0,0 +1,133 @@
+use std::{
+    ops::Bound,
+    sync::{Arc, Mutex},
+};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::test_util::make_seed;
+
+type Events = Arc<Mutex<Vec<Event<i64, i64>>>>;
+
+fn watcher(events: &Events) -> impl FnMut(&Event<i64, i64>) + Send + Sync {
+    let events = Arc::clone(events);
+    move |event| events.lock().unwrap().push(event.clone())
+}
+
+#[test]
+fn test_watch() {
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-watch");
+    let (a, b, c, d): (Events, Events, Events, Events) = Default::default();
+    let ida = llrb.watch(..10, watcher(&a));
+    let idb = llrb.watch(5..=15, watcher(&b));
+    let idc = llrb.watch((Bound::Excluded(15), Bound::Unbounded), watcher(&c));
+    let idd = llrb.watch(5..=15, watcher(&d));
+    assert_ne!(idb, idd);
+
+    for key in [0, 5, 10, 15, 16].iter() {
+        llrb.set(*key, *key);
+    }
+    llrb.set(10, 100);
+    llrb.delete(&5);
+    assert!(llrb.unwatch(idd));
+    assert!(!llrb.unwatch(idd));
+    llrb.delete(&15);
+    llrb.clear();
+
+    let refs = vec![
+        Event::Inserted(0, 0),
+        Event::Inserted(5, 5),
+        Event::Deleted(5, 5),
+        Event::Cleared,
+    ];
+    assert_eq!(*a.lock().unwrap(), refs);
+    let refs = vec![
+        Event::Inserted(5, 5),
+        Event::Inserted(10, 10),
+        Event::Inserted(15, 15),
+        Event::Updated(10, 10, 100),
+        Event::Deleted(5, 5),
+        Event::Deleted(15, 15),
+        Event::Cleared,
+    ];
+    assert_eq!(*b.lock().unwrap(), refs);
+    let refs = vec![Event::Inserted(16, 16), Event::Cleared];
+    assert_eq!(*c.lock().unwrap(), refs);
+    assert_eq!(d.lock().unwrap()[..], b.lock().unwrap()[..5]);
+
+    assert!(llrb.unwatch(ida));
+    assert!(llrb.unwatch(idb));
+    assert!(llrb.unwatch(idc));
+    llrb.set(0, 0);
+    assert_eq!(a.lock().unwrap().len(), 4);
+
+    // clone does not carry watches.
+    let mut cloned = llrb.clone();
+    llrb.watch(.., watcher(&a));
+    cloned.set(1, 1);
+    assert_eq!(a.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn test_watch_random() {
+    let seed = make_seed();
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    println!("seed {}", seed);
+
+    let mut llrb: Llrb<i64, i64> = Llrb::new("test-watch-random");
+    let all: Events = Default::default();
+    llrb.observe(watcher(&all));
+
+    let mut watches = vec![];
+    for _ in 0..100 {
+        let (a, b) = (rng.gen_range(-10, 110), rng.gen_range(-10, 110));
+        let (low, high) = (a.min(b), a.max(b));
+        let low = match rng.gen_range(0, 3) {
+            0 => Bound::Included(low),
+            1 => Bound::Excluded(low),
+            _ => Bound::Unbounded,
+        };
+        let high = match rng.gen_range(0, 3) {
+            0 => Bound::Included(high),
+            1 => Bound::Excluded(high),
+            _ => Bound::Unbounded,
+        };
+        let events: Events = Default::default();
+        let id = llrb.watch((low, high), watcher(&events));
+        watches.push((id, (low, high), events, 0));
+    }
+
+    for i in 0..10_000 {
+        let key = rng.gen_range(0, 100);
+        match rng.gen_range(0, 10) {
+            0..=5 => {
+                llrb.set(key, i);
+            }
+            6..=8 => {
+                llrb.delete(&key);
+            }
+            _ if rng.gen_range(0, 100) == 0 => llrb.clear(),
+            _ => {
+                // re-register a watch, afresh.
+                let off = rng.gen_range(0, watches.len());
+                let (id, range, events, _) = watches.remove(off);
+                assert!(llrb.unwatch(id));
+                events.lock().unwrap().clear();
+                let n = all.lock().unwrap().len();
+                let id = llrb.watch(range, watcher(&events));
+                watches.push((id, range, events, n));
+            }
+        }
+    }
+
+    let all = all.lock().unwrap();
+    for (_, range, events, n) in watches.iter() {
+        let refs: Vec<Event<i64, i64>> = all[*n..]
+            .iter()
+            .filter(|event| event.key().is_none_or(|key| range.contains(key)))
+            .cloned()
+            .collect();
+        assert_eq!(*events.lock().unwrap(), refs);
+    }
+}